@@ -1,7 +1,19 @@
-pub const MAX_ASSETS: usize = 33;
-pub const MAX_POSITIONS: usize = 16; // Match MAX_ASSETS to allow position in every asset
+// bound on market.max_assets; the asset, risk and price registries are sized
+// by the registered count and grow on register_asset
+pub const MAX_ASSETS: usize = 128;
+pub const MAX_POSITIONS: usize = 33; // rent is paid per position held
 
-pub const MAX_RISK_PAIRS: usize = (MAX_ASSETS * (MAX_ASSETS + 1)) / 2;
+pub const MAX_QUEUED_CHANGES: usize = 16;
+// shortest timelock a param queue accepts, so queued changes stay visible
+pub const MIN_PARAM_DELAY_SECS: i64 = 24 * 60 * 60;
+pub const MAX_RATE_CURVE_POINTS: usize = 8;
+// fastest adaptive curve drift, 50x per year at full error: about 14% a day
+pub const MAX_ADJUSTMENT_SPEED_BPS: u32 = 500_000;
+
+// 1: original Borsh layouts
+// 2: AssetMeta borrow flag and oracle settings, zero-copy directional
+//    RiskRegistry and PriceCache with Pyth spot, EMA and confidence
+pub const MARKET_VERSION: u8 = 2;
 
 pub const SEED_MARKET: &[u8] = b"market";
 pub const SEED_ASSET_REG: &[u8] = b"asset-reg";
@@ -11,6 +23,7 @@ pub const SEED_POOL: &[u8] = b"pool";
 pub const SEED_VAULT: &[u8] = b"vault";
 pub const SEED_VAULT_AUTH: &[u8] = b"vault-auth";
 pub const SEED_OBLIGATION: &[u8] = b"obligation";
+pub const SEED_PARAM_QUEUE: &[u8] = b"param-queue";
 pub const SEED_FAUCET_MINT: &[u8] = b"faucet-mint";
 pub const SEED_FAUCET_MINT_AUTH: &[u8] = b"faucet-mint-auth";
 
@@ -18,4 +31,9 @@ pub const SECS_YEAR: u64 = 365 * 24 * 60 * 60;
 
 pub const BPS_DENOM: u64 = 10_000;
 
-pub const MAX_BORROW_APY_BPS_HARD: u16 = 100_00; // 100% APY
+pub const MAX_BORROW_APY_BPS_HARD: u16 = 10_000; // 100% APY
+
+pub const MAX_LIQ_BONUS_BPS: u16 = 5_000; // 50%
+
+pub const MAX_CONF_BPS: u16 = 2_000; // widest Pyth conf an asset may accept, 20% of price
+pub const MAX_CONF_MULTIPLIER_BPS: u16 = 10_000; // health moves prices at most 1x conf
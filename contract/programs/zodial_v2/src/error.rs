@@ -50,4 +50,52 @@ pub enum ZodialError {
     PriceNotFound,
     #[msg("Pool has active positions and cannot be closed")]
     PoolNotEmpty,
+    #[msg("Invalid market configuration")]
+    InvalidMarketConfig,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Signer lacks the required market role")]
+    MissingRole,
+    #[msg("Parameter change queue is full")]
+    ParamQueueFull,
+    #[msg("Queued change not found")]
+    QueuedChangeNotFound,
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Rate model parameters out of bounds")]
+    InvalidRateModel,
+    #[msg("Asset is not enabled for borrowing")]
+    BorrowDisabled,
+    #[msg("Deposit would exceed the pool supply cap")]
+    SupplyCapExceeded,
+    #[msg("Borrow would exceed the pool borrow cap")]
+    BorrowCapExceeded,
+    #[msg("Destination is not the treasury token account")]
+    InvalidTreasury,
+    #[msg("Pool passed more than once")]
+    DuplicatePool,
+    #[msg("Pool for an obligation position was not passed")]
+    MissingPool,
+    #[msg("Pool does not back any obligation position")]
+    UnexpectedPool,
+    #[msg("Risk pair asset index out of range")]
+    RiskPairIndexOutOfRange,
+    #[msg("Risk pair LTV exceeds its liquidation threshold")]
+    LtvAboveThreshold,
+    #[msg("Risk pair liquidation threshold must be below 100%")]
+    LiqThresholdTooHigh,
+    #[msg("Risk pair liquidation bonus leaves seized collateral at or above 100% of debt")]
+    LiqBonusTooHigh,
+    #[msg("Price confidence interval is wider than the asset allows")]
+    PriceConfidenceTooWide,
+    #[msg("Number of price update accounts does not match the mints")]
+    PriceUpdateAccountsMismatch,
+    #[msg("Pyth exponent outside the supported range")]
+    PythExponentOutOfRange,
+    #[msg("Asset is not enabled as collateral")]
+    CollateralDisabled,
+    #[msg("Tightening a risk pair must go through the param queue")]
+    RiskPairTightened,
+    #[msg("Asset confidence settings out of bounds")]
+    InvalidConfSettings,
 }
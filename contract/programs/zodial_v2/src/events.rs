@@ -1,13 +1,73 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{OracleSource, ParamChange, RateModel};
+
 #[event]
 pub struct MarketInitialized {
     pub market: Pubkey,
     pub authority: Pubkey,
+    pub market_id: u64,
     pub max_assets: u16,
     pub max_positions: u16,
 }
 
+#[event]
+pub struct AuthorityProposed {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityAccepted {
+    pub market: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct RegistriesMigrated {
+    pub market: Pubkey,
+    pub market_migrated: bool,
+    pub asset_registry_migrated: bool,
+    pub risk_registry_migrated: bool,
+    pub price_cache_migrated: bool,
+    pub version: u8,
+}
+
+#[event]
+pub struct PoolMigrated {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+}
+
+#[event]
+pub struct MarketRolesUpdated {
+    pub market: Pubkey,
+    pub risk_admin: Pubkey,
+    pub oracle_admin: Pubkey,
+    pub pause_guardian: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct MarketConfigUpdated {
+    pub market: Pubkey,
+    pub old_paused: bool,
+    pub new_paused: bool,
+    pub old_max_positions: u16,
+    pub new_max_positions: u16,
+    pub old_default_ltv_bps: u16,
+    pub new_default_ltv_bps: u16,
+    pub old_default_liq_threshold_bps: u16,
+    pub new_default_liq_threshold_bps: u16,
+    pub old_default_liq_bonus_bps: u16,
+    pub new_default_liq_bonus_bps: u16,
+    pub old_pyth_max_age_secs: u64,
+    pub new_pyth_max_age_secs: u64,
+}
+
 #[event]
 pub struct AssetRegistered {
     pub market: Pubkey,
@@ -15,6 +75,18 @@ pub struct AssetRegistered {
     pub index: u16,
 }
 
+#[event]
+pub struct AssetFlagsUpdated {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub enabled_as_collateral: bool,
+    pub enabled_for_borrow: bool,
+    pub max_price_age_secs: u32,
+    pub max_conf_bps: u16,
+    pub conf_multiplier_bps: u16,
+    pub oracle: OracleSource,
+}
+
 #[event]
 pub struct PoolInitialized {
     pub market: Pubkey,
@@ -22,6 +94,35 @@ pub struct PoolInitialized {
     pub pool: Pubkey,
 }
 
+#[event]
+pub struct PoolRateModelUpdated {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+    pub old_rate: RateModel,
+    pub new_rate: RateModel,
+}
+
+#[event]
+pub struct PoolAccrued {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+    pub borrow_fac_q60: u128,
+    pub deposit_fac_q60: u128,
+    pub accumulated_reserves_q60: u128,
+    pub utilization_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolCapsUpdated {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub supply_cap: u64,
+    pub borrow_cap: u64,
+}
+
 #[event]
 pub struct Deposit {
     pub market: Pubkey,
@@ -58,6 +159,15 @@ pub struct Withdraw {
     pub burned_shares_q60: u128,
 }
 
+#[event]
+pub struct ReservesClaimed {
+    pub market: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub remaining_reserves_q60: u128,
+}
+
 #[event]
 pub struct RiskPairSet {
     pub market: Pubkey,
@@ -76,10 +186,32 @@ pub struct RiskPairsBatchSet {
     pub count: u16,
 }
 
+#[event]
+pub struct ParamChangeQueued {
+    pub market: Pubkey,
+    pub id: u64,
+    pub eta: i64,
+    pub change: ParamChange,
+}
+
+#[event]
+pub struct ParamChangeExecuted {
+    pub market: Pubkey,
+    pub id: u64,
+    pub change: ParamChange,
+}
+
+#[event]
+pub struct ParamChangeCancelled {
+    pub market: Pubkey,
+    pub id: u64,
+}
+
 #[event]
 pub struct PricesUpdated {
     pub market: Pubkey,
-    pub count: u16,
+    pub count: u16,   // prices written
+    pub skipped: u16, // entries left untouched, see update_prices_pyth_batch
     pub slot: u64,
 }
 
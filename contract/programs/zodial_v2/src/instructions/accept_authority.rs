@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, error::ZodialError, events::AuthorityAccepted, state::Market};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.pending_authority != Pubkey::default() @ ZodialError::NotPendingAuthority,
+        constraint = market.pending_authority == new_authority.key() @ ZodialError::NotPendingAuthority
+    )]
+    pub market: Account<'info, Market>,
+}
+
+/// Step 2 of the handover: the nominated key signs to take over the market.
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let m = &mut ctx.accounts.market;
+    let old_authority = m.authority;
+
+    m.authority = ctx.accounts.new_authority.key();
+    m.pending_authority = Pubkey::default();
+
+    emit!(AuthorityAccepted {
+        market: m.key(),
+        old_authority,
+        new_authority: m.authority,
+    });
+
+    Ok(())
+}
@@ -9,11 +9,13 @@ use crate::{
     error::ZodialError,
     events::Borrow as BorrowEvent,
     signer_seeds_vault_auth,
-    state::{AssetRegistry, Market, Obligation, Pool, Position, PriceCache, RiskRegistry},
+    state::{AssetRegistry, Market, Obligation, Pool, PriceCache, PriceTable, RiskMatrix, RiskRegistry},
     utils::{
         accrual::accrue_pool,
         health::assert_healthy_at_least_1,
-        math::{div_u64_by_u68_to_q60, unpack_u68f60},
+        math::{div_ceil, unpack_u68f60},
+        pools::load_obligation_pools,
+        realloc::grow_account,
     },
 };
 
@@ -29,16 +31,12 @@ pub struct Borrow<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
-    /// CHECK: read only
-    pub authority: UncheckedAccount<'info>,
-
     #[account(
         seeds = [SEED_ASSET_REG, market.key().as_ref()],
         bump = asset_registry.bump
@@ -47,15 +45,15 @@ pub struct Borrow<'info> {
 
     #[account(
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     #[account(
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Option<Account<'info, PriceCache>>,
+    pub price_cache: Option<AccountLoader<'info, PriceCache>>,
 
     /// The mint for this pool
     #[account(
@@ -107,34 +105,34 @@ pub struct Borrow<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     // other Pool accounts for assets in `obligation.positions`
-    // should be passed in remaining_accounts
+    // should be passed (writable) in remaining_accounts
 }
 
 pub fn borrow(ctx: Context<Borrow>, args: BorrowArgs) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
-    accrue_pool(&mut ctx.accounts.pool, now);
+    let asset = ctx
+        .accounts
+        .asset_registry
+        .assets
+        .iter()
+        .find(|a| a.mint == ctx.accounts.pool.mint)
+        .ok_or(error!(ZodialError::AssetNotRegistered))?;
+    require!(asset.enabled_for_borrow, ZodialError::BorrowDisabled);
+
+    accrue_pool(&mut ctx.accounts.pool, now)?;
+    ctx.accounts.pool.check_borrow_cap(args.amount)?;
 
     let b_idx = unpack_u68f60(ctx.accounts.pool.borrow_fac_q60);
-    let add_borrow_shares_q60 = div_u64_by_u68_to_q60(args.amount, b_idx)?;
+    // debt is recorded rounded up
+    let add_borrow_shares_q60 = div_ceil(args.amount, b_idx)?;
 
     let mut ob_sim = ctx.accounts.obligation.clone();
-    if let Some(pos) = ob_sim
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == ctx.accounts.pool.mint)
-    {
-        pos.borrow_shares_q60 = pos
-            .borrow_shares_q60
-            .checked_add(add_borrow_shares_q60)
-            .ok_or(error!(ZodialError::MathOverflow))?;
-    } else {
-        ob_sim.positions.push(Position {
-            mint: ctx.accounts.pool.mint,
-            deposit_shares_q60: 0,
-            borrow_shares_q60: add_borrow_shares_q60,
-        });
-    }
+    ob_sim.add_borrow_shares(
+        ctx.accounts.pool.mint,
+        add_borrow_shares_q60,
+        ctx.accounts.market.max_positions,
+    )?;
 
     let mut pools: Vec<Pool> = Vec::new();
     {
@@ -145,55 +143,43 @@ pub fn borrow(ctx: Context<Borrow>, args: BorrowArgs) -> Result<()> {
             .ok_or(error!(ZodialError::MathOverflow))?;
         pools.push(touched);
 
-        for ai in ctx.remaining_accounts.iter() {
-            // Only accept accounts owned by this program
-            require!(ai.owner == &crate::id(), ZodialError::Unauthorized);
-            // Deserialize Pool
-            let mut data: &[u8] = &ai.data.borrow();
-            let pool = Pool::try_deserialize(&mut data)?;
-            // Seed re-derivation for safety
-            let (expect, _b) = Pubkey::find_program_address(
-                &[
-                    SEED_POOL,
-                    ctx.accounts.market.key().as_ref(),
-                    pool.mint.as_ref(),
-                ],
-                &crate::id(),
-            );
-            require!(expect == *ai.key, ZodialError::Unauthorized);
-            pools.push(pool);
-        }
+        pools.extend(load_obligation_pools(
+            ctx.remaining_accounts,
+            &ctx.accounts.market.key(),
+            &ctx.accounts.obligation,
+            &[ctx.accounts.pool.mint],
+            now,
+        )?);
     }
 
     // Health check
-    let price_cache_ref = ctx.accounts.price_cache.as_ref().map(|a| &**a);
+    let price_cache_data = ctx
+        .accounts
+        .price_cache
+        .as_ref()
+        .map(PriceCache::data)
+        .transpose()?;
+    let price_cache = price_cache_data
+        .as_deref()
+        .map(PriceTable::new)
+        .transpose()?;
+    let price_cache_ref = price_cache.as_ref();
     let _health = assert_healthy_at_least_1(
         &ob_sim,
         &ctx.accounts.market,
         &ctx.accounts.asset_registry,
-        &ctx.accounts.risk_registry,
+        &RiskMatrix::new(&*RiskRegistry::data(&ctx.accounts.risk_registry)?)?,
         price_cache_ref,
         &pools,
+        now,
     )?;
 
     {
-        let ob = &mut ctx.accounts.obligation;
-        if let Some(pos) = ob
-            .positions
-            .iter_mut()
-            .find(|p| p.mint == ctx.accounts.pool.mint)
-        {
-            pos.borrow_shares_q60 = pos
-                .borrow_shares_q60
-                .checked_add(add_borrow_shares_q60)
-                .ok_or(error!(ZodialError::MathOverflow))?;
-        } else {
-            ob.positions.push(Position {
-                mint: ctx.accounts.pool.mint,
-                deposit_shares_q60: 0,
-                borrow_shares_q60: add_borrow_shares_q60,
-            });
-        }
+        ctx.accounts.obligation.add_borrow_shares(
+            ctx.accounts.pool.mint,
+            add_borrow_shares_q60,
+            ctx.accounts.market.max_positions,
+        )?;
 
         let p = &mut ctx.accounts.pool;
         p.total_borrow_shares_q60 = p
@@ -202,6 +188,15 @@ pub fn borrow(ctx: Context<Borrow>, args: BorrowArgs) -> Result<()> {
             .ok_or(error!(ZodialError::MathOverflow))?;
     }
 
+    // the owner pays rent for any position added above
+    let len = Obligation::space(ctx.accounts.obligation.positions.len());
+    grow_account(
+        &ctx.accounts.obligation.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        len,
+    )?;
+
     // Liquidity check + transfer
     require!(
         ctx.accounts.pool_vault.amount >= args.amount,
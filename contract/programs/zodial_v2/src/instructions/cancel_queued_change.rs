@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::ParamChangeCancelled,
+    state::{Market, ParamChangeQueue, Role},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CancelQueuedChangeArgs {
+    pub id: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: CancelQueuedChangeArgs)]
+pub struct CancelQueuedChange<'info> {
+    /// Market authority or risk admin
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [SEED_PARAM_QUEUE, market.key().as_ref()],
+        bump = param_queue.bump
+    )]
+    pub param_queue: Account<'info, ParamChangeQueue>,
+}
+
+pub fn cancel_queued_change(
+    ctx: Context<CancelQueuedChange>,
+    args: CancelQueuedChangeArgs,
+) -> Result<()> {
+    let q = &mut ctx.accounts.param_queue;
+    let i = q
+        .changes
+        .iter()
+        .position(|c| c.id == args.id)
+        .ok_or(error!(ZodialError::QueuedChangeNotFound))?;
+    q.changes.remove(i);
+
+    emit!(ParamChangeCancelled {
+        market: ctx.accounts.market.key(),
+        id: args.id,
+    });
+
+    Ok(())
+}
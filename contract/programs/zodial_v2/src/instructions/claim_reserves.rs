@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use fixed::types::U68F60;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::ReservesClaimed,
+    signer_seeds_vault_auth,
+    state::{Market, Pool, Role},
+    utils::accrual::accrue_pool,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimReservesArgs {
+    pub amount: u64, // capped to accrued reserves and vault balance
+}
+
+#[derive(Accounts)]
+#[instruction(args: ClaimReservesArgs)]
+pub struct ClaimReserves<'info> {
+    /// Market authority or treasury
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.has_role(Role::Treasury, &authority.key()) @ ZodialError::MissingRole
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        has_one = market,
+        seeds = [SEED_POOL, market.key().as_ref(), pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        constraint = pool_vault.key() == pool.vault @ ZodialError::Unauthorized,
+        constraint = pool_vault.mint == pool.mint @ ZodialError::InvalidMint
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA authority for vault
+    #[account(
+        seeds = [SEED_VAULT_AUTH, pool.key().as_ref()],
+        bump = pool.vault_auth_bump
+    )]
+    pub vault_auth: UncheckedAccount<'info>,
+
+    /// Owned by the treasury role, or by the authority while none is assigned
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == pool.mint @ ZodialError::InvalidMint,
+        constraint = treasury_token_account.owner == market.treasury() @ ZodialError::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn claim_reserves(ctx: Context<ClaimReserves>, args: ClaimReservesArgs) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    accrue_pool(&mut ctx.accounts.pool, now)?;
+
+    let available: u64 = U68F60::from_bits(ctx.accounts.pool.accumulated_reserves_q60)
+        .to_num::<u128>()
+        .try_into()
+        .unwrap_or(u64::MAX);
+    let amount = args
+        .amount
+        .min(available)
+        .min(ctx.accounts.pool_vault.amount);
+    if amount == 0 {
+        return Ok(());
+    }
+
+    {
+        let p = &mut ctx.accounts.pool;
+        p.accumulated_reserves_q60 = p
+            .accumulated_reserves_q60
+            .checked_sub(U68F60::from_num(amount).to_bits())
+            .ok_or(error!(ZodialError::MathUnderflow))?;
+    }
+
+    let key = ctx.accounts.pool.key();
+    let seeds = signer_seeds_vault_auth!(key, ctx.accounts.pool.vault_auth_bump);
+    let signer_seeds: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.pool_vault.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.vault_auth.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer(cpi, amount)?;
+
+    emit!(ReservesClaimed {
+        market: ctx.accounts.market.key(),
+        mint: ctx.accounts.pool.mint,
+        destination: ctx.accounts.treasury_token_account.key(),
+        amount,
+        remaining_reserves_q60: ctx.accounts.pool.accumulated_reserves_q60,
+    });
+
+    Ok(())
+}
@@ -8,7 +8,7 @@ pub struct CloseMarket<'info> {
         mut,
         close = receiver,
         has_one = authority @ ZodialError::Unauthorized,
-        seeds = [b"market", authority.key().as_ref()],
+        seeds = [b"market", market.pda_seed().as_ref()],
         bump = market.bump
     )]
     pub market: Account<'info, Market>,
@@ -10,7 +10,7 @@ pub struct ClosePool<'info> {
 
     #[account(
         has_one = authority @ ZodialError::Unauthorized,
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump
     )]
     pub market: Account<'info, Market>,
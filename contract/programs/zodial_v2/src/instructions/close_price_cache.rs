@@ -16,9 +16,9 @@ pub struct ClosePriceCache<'info> {
         mut,
         close = receiver,
         seeds = [b"price-cache", market.key().as_ref()],
-        bump = price_cache.bump
+        bump = price_cache.load()?.bump
     )]
-    pub price_cache: Account<'info, PriceCache>,
+    pub price_cache: AccountLoader<'info, PriceCache>,
 
     /// Receiver of the reclaimed rent
     #[account(mut)]
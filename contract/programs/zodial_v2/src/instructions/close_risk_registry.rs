@@ -16,9 +16,9 @@ pub struct CloseRiskRegistry<'info> {
         mut,
         close = receiver,
         seeds = [b"risk-reg", market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     /// Receiver of the reclaimed rent
     #[account(mut)]
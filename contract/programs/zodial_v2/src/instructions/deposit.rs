@@ -5,10 +5,11 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::Deposit as DepositEvent,
-    state::{AssetRegistry, Market, Obligation, Pool, Position},
+    state::{AssetRegistry, Market, Obligation, Pool},
     utils::{
         accrual::accrue_pool,
-        math::{div_u64_by_u68_to_q60, unpack_u68f60},
+        math::{div_floor, unpack_u68f60},
+        realloc::grow_account,
     },
 };
 
@@ -24,16 +25,12 @@ pub struct Deposit<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, market.authority.as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
-    /// CHECK: read-only
-    pub authority: UncheckedAccount<'info>,
-
     #[account(
         seeds = [SEED_ASSET_REG, market.key().as_ref()],
         bump = asset_registry.bump
@@ -71,7 +68,7 @@ pub struct Deposit<'info> {
     #[account(
         init_if_needed,
         payer = owner,
-        space = 8 + Obligation::INIT_SPACE,
+        space = Obligation::space(1),
         seeds = [SEED_OBLIGATION, market.key().as_ref(), owner.key().as_ref()],
         bump
     )]
@@ -84,10 +81,12 @@ pub struct Deposit<'info> {
 pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
-    accrue_pool(&mut ctx.accounts.pool, now);
+    accrue_pool(&mut ctx.accounts.pool, now)?;
+    ctx.accounts.pool.check_supply_cap(args.amount)?;
 
     let deposit_idx = unpack_u68f60(ctx.accounts.pool.deposit_fac_q60);
-    let shares_q60 = div_u64_by_u68_to_q60(args.amount, deposit_idx)?;
+    // deposits mint shares rounded down
+    let shares_q60 = div_floor(args.amount, deposit_idx)?;
 
     {
         let ob = &mut ctx.accounts.obligation;
@@ -117,29 +116,22 @@ pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
             });
         }
 
-        if let Some(pos) = ob
-            .positions
-            .iter_mut()
-            .find(|p| p.mint == ctx.accounts.pool.mint)
-        {
-            pos.deposit_shares_q60 = pos
-                .deposit_shares_q60
-                .checked_add(shares_q60)
-                .ok_or(error!(ZodialError::MathOverflow))?;
-        } else {
-            require!(
-                ob.positions.len() < ctx.accounts.market.max_positions as usize,
-                ZodialError::ExceedsMaxPositions
-            );
-
-            ob.positions.push(Position {
-                mint: ctx.accounts.pool.mint,
-                deposit_shares_q60: shares_q60,
-                borrow_shares_q60: 0u128,
-            });
-        }
+        ob.add_deposit_shares(
+            ctx.accounts.pool.mint,
+            shares_q60,
+            ctx.accounts.market.max_positions,
+        )?;
     }
 
+    // the owner pays rent for any position added above
+    let len = Obligation::space(ctx.accounts.obligation.positions.len());
+    grow_account(
+        &ctx.accounts.obligation.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        len,
+    )?;
+
     {
         let p = &mut ctx.accounts.pool;
         p.total_deposit_shares_q60 = p
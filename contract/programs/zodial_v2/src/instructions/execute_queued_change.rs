@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::{ParamChangeExecuted, PoolRateModelUpdated},
+    state::{
+        AssetRegistry, Market, ParamChange, ParamChangeQueue, Pool, RiskMatrix, RiskPair,
+        RiskRegistry,
+    },
+    utils::accrual::accrue_pool,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExecuteQueuedChangeArgs {
+    pub id: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ExecuteQueuedChangeArgs)]
+pub struct ExecuteQueuedChange<'info> {
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [SEED_PARAM_QUEUE, market.key().as_ref()],
+        bump = param_queue.bump
+    )]
+    pub param_queue: Account<'info, ParamChangeQueue>,
+
+    #[account(
+        seeds = [SEED_ASSET_REG, market.key().as_ref()],
+        bump = asset_registry.bump
+    )]
+    pub asset_registry: Account<'info, AssetRegistry>,
+
+    #[account(
+        mut,
+        seeds = [SEED_RISK_REG, market.key().as_ref()],
+        bump = risk_registry.load()?.bump
+    )]
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
+
+    /// Only required for `ParamChange::PoolRateModel`
+    #[account(
+        mut,
+        seeds = [SEED_POOL, market.key().as_ref(), pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Option<Account<'info, Pool>>,
+}
+
+/// Permissionless: anyone can apply a queued change once its eta has passed.
+pub fn execute_queued_change(
+    ctx: Context<ExecuteQueuedChange>,
+    args: ExecuteQueuedChangeArgs,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    let i = ctx
+        .accounts
+        .param_queue
+        .changes
+        .iter()
+        .position(|c| c.id == args.id)
+        .ok_or(error!(ZodialError::QueuedChangeNotFound))?;
+    let queued = ctx.accounts.param_queue.changes[i].clone();
+    require!(now >= queued.eta, ZodialError::TimelockNotElapsed);
+
+    match &queued.change {
+        ParamChange::RiskPair {
+            a_index,
+            b_index,
+            ltv_bps,
+            liq_threshold_bps,
+            liq_bonus_bps,
+        } => {
+            let mut rr_data = RiskRegistry::data_mut(&ctx.accounts.risk_registry)?;
+            RiskMatrix::new(&mut *rr_data)?.set_pair(
+                *a_index,
+                *b_index,
+                RiskPair {
+                    ltv_bps: *ltv_bps,
+                    liq_threshold_bps: *liq_threshold_bps,
+                    liq_bonus_bps: *liq_bonus_bps,
+                },
+            )?;
+        }
+        ParamChange::PoolRateModel { mint, rate } => {
+            let pool = ctx
+                .accounts
+                .pool
+                .as_mut()
+                .ok_or(error!(ZodialError::PoolNotFound))?;
+            require_keys_eq!(pool.mint, *mint, ZodialError::InvalidMint);
+            rate.validate()?;
+            // settle past interest at the old curve
+            accrue_pool(pool, now)?;
+            let old_rate = std::mem::replace(&mut pool.rate, rate.clone());
+
+            emit!(PoolRateModelUpdated {
+                market: ctx.accounts.market.key(),
+                mint: pool.mint,
+                pool: pool.key(),
+                old_rate,
+                new_rate: rate.clone(),
+            });
+        }
+        ParamChange::Delay { delay_secs } => {
+            ctx.accounts.param_queue.delay_secs = *delay_secs;
+        }
+    }
+
+    ctx.accounts.param_queue.changes.remove(i);
+
+    emit!(ParamChangeExecuted {
+        market: ctx.accounts.market.key(),
+        id: queued.id,
+        change: queued.change,
+    });
+
+    Ok(())
+}
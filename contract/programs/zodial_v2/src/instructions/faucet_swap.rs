@@ -8,7 +8,7 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::FaucetSwapped,
-    state::{AssetRegistry, FaucetMint, PriceCache},
+    state::{AssetRegistry, FaucetMint, PriceCache, PriceTable},
     utils::math::unpack_u68f60,
 };
 
@@ -86,9 +86,9 @@ pub struct FaucetSwap<'info> {
     /// Price cache to get current prices
     #[account(
         seeds = [SEED_PRICE_CACHE, faucet_mint_from.market.as_ref()],
-        bump = price_cache.bump
+        bump = price_cache.load()?.bump
     )]
-    pub price_cache: Account<'info, PriceCache>,
+    pub price_cache: AccountLoader<'info, PriceCache>,
 
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -124,24 +124,19 @@ pub fn faucet_swap(ctx: Context<FaucetSwap>, args: FaucetSwapArgs) -> Result<()>
         .ok_or(ZodialError::AssetNotRegistered)?;
 
     // Step 2: Get prices from cache
-    let price_from_entry = ctx
-        .accounts
-        .price_cache
-        .prices
-        .iter()
-        .find(|p| p.asset_index == asset_from.index)
-        .ok_or(ZodialError::PriceNotFound)?;
-
-    let price_to_entry = ctx
-        .accounts
-        .price_cache
-        .prices
-        .iter()
-        .find(|p| p.asset_index == asset_to.index)
-        .ok_or(ZodialError::PriceNotFound)?;
+    let (price_from_q60, price_to_q60) = {
+        let pc_data = PriceCache::data(&ctx.accounts.price_cache)?;
+        let pc = PriceTable::new(&*pc_data)?;
+        (
+            pc.price_q60(asset_from.index)
+                .ok_or(ZodialError::PriceNotFound)?,
+            pc.price_q60(asset_to.index)
+                .ok_or(ZodialError::PriceNotFound)?,
+        )
+    };
 
-    let price_from = unpack_u68f60(price_from_entry.price_q60);
-    let price_to = unpack_u68f60(price_to_entry.price_q60);
+    let price_from = unpack_u68f60(price_from_q60);
+    let price_to = unpack_u68f60(price_to_q60);
 
     // Step 3: Calculate swap amount based on prices
     // Formula: amount_to = (amount_from * price_from / price_to) * (10^decimals_to / 10^decimals_from)
@@ -21,7 +21,7 @@ pub struct InitFaucetMint<'info> {
     pub authority: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
         has_one = authority
     )]
@@ -3,11 +3,12 @@ use anchor_lang::prelude::*;
 use crate::{
     constants::*,
     events::MarketInitialized,
-    state::{AssetRegistry, Market, PriceCache, PriceMode, RiskRegistry},
+    state::{AssetRegistry, Market, MarketRoles, PriceCache, PriceMode, RiskRegistry},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct InitMarketArgs {
+    pub market_id: u64,
     pub max_assets: u16,
     pub max_positions: u16,
     pub default_ltv_bps: u16,
@@ -29,7 +30,7 @@ pub struct InitMarket<'info> {
         init,
         payer = payer,
         space = 8 + Market::INIT_SPACE,
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, args.market_id.to_le_bytes().as_ref()],
         bump
     )]
     pub market: Account<'info, Market>,
@@ -37,7 +38,7 @@ pub struct InitMarket<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + AssetRegistry::INIT_SPACE,
+        space = AssetRegistry::space(0),
         seeds = [SEED_ASSET_REG, market.key().as_ref()],
         bump
     )]
@@ -46,20 +47,20 @@ pub struct InitMarket<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + RiskRegistry::INIT_SPACE,
+        space = RiskRegistry::space(0),
         seeds = [SEED_RISK_REG, market.key().as_ref()],
         bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     #[account(
         init,
         payer = payer,
-        space = 8 + PriceCache::INIT_SPACE,
+        space = PriceCache::space(0),
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump
     )]
-    pub price_cache: Account<'info, PriceCache>,
+    pub price_cache: AccountLoader<'info, PriceCache>,
 
     pub system_program: Program<'info, System>,
 }
@@ -80,17 +81,23 @@ pub fn init_market(ctx: Context<InitMarket>, args: InitMarketArgs) -> Result<()>
     {
         let m = &mut ctx.accounts.market;
         m.authority = ctx.accounts.authority.key();
+        m.pending_authority = Pubkey::default();
+        m.market_id = args.market_id;
+        m.legacy_seed = Pubkey::default();
         m.max_assets = args.max_assets;
         m.max_positions = args.max_positions;
         m.default_ltv_bps = args.default_ltv_bps;
         m.default_liq_threshold_bps = args.default_liq_threshold_bps;
         m.default_liq_bonus_bps = args.default_liq_bonus_bps;
         m.price_mode = args.price_mode;
-        m.version = 1;
+        m.version = MARKET_VERSION;
         m.bump = market_bump;
         m.price_cache_bump = price_cache_bump;
         m.paused = false;
         m.pyth_max_age_secs = args.pyth_max_age_secs;
+        m.roles = MarketRoles::default();
+        // defaults fill every new pair, so they obey the same invariants
+        m.default_risk_pair().validate()?;
     }
 
     {
@@ -103,25 +110,24 @@ pub fn init_market(ctx: Context<InitMarket>, args: InitMarketArgs) -> Result<()>
 
     // Risk registry
     {
-        let rr = &mut ctx.accounts.risk_registry;
+        let mut rr = ctx.accounts.risk_registry.load_init()?;
         rr.market = ctx.accounts.market.key();
         rr.bump = ctx.bumps.risk_registry;
-        rr.dim = 0;
-        rr.pairs = Vec::with_capacity(0); // append on asset register
+        rr.dim = 0; // grows on asset register
     }
 
     // Price cache
     {
-        let pc = &mut ctx.accounts.price_cache;
+        let mut pc = ctx.accounts.price_cache.load_init()?;
         pc.market = ctx.accounts.market.key();
         pc.bump = price_cache_bump;
         pc.last_slot = 0;
-        pc.prices = Vec::with_capacity(0);
     }
 
     emit!(MarketInitialized {
         market: ctx.accounts.market.key(),
         authority: ctx.accounts.authority.key(),
+        market_id: args.market_id,
         max_assets: args.max_assets,
         max_positions: args.max_positions,
     });
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    state::{Market, ParamChangeQueue},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitParamQueueArgs {
+    pub delay_secs: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: InitParamQueueArgs)]
+pub struct InitParamQueue<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        has_one = authority @ ZodialError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ParamChangeQueue::INIT_SPACE,
+        seeds = [SEED_PARAM_QUEUE, market.key().as_ref()],
+        bump
+    )]
+    pub param_queue: Account<'info, ParamChangeQueue>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_param_queue(ctx: Context<InitParamQueue>, args: InitParamQueueArgs) -> Result<()> {
+    require!(
+        args.delay_secs >= MIN_PARAM_DELAY_SECS,
+        ZodialError::InvalidMarketConfig
+    );
+
+    let q = &mut ctx.accounts.param_queue;
+    q.market = ctx.accounts.market.key();
+    q.bump = ctx.bumps.param_queue;
+    q.delay_secs = args.delay_secs;
+    q.next_id = 0;
+    q.changes = Vec::with_capacity(0);
+
+    Ok(())
+}
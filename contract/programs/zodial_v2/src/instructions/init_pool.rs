@@ -7,7 +7,7 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::PoolInitialized,
-    state::{AssetRegistry, Market, Pool, RateModel},
+    state::{AssetRegistry, Market, Pool, RateModel, Role},
     utils::math::pack_u68f60,
 };
 
@@ -15,6 +15,8 @@ use crate::{
 pub struct InitPoolArgs {
     pub rate: RateModel,
     pub mint: Pubkey,
+    pub supply_cap: u64, // 0 = uncapped
+    pub borrow_cap: u64, // 0 = uncapped
 }
 
 #[derive(Accounts)]
@@ -23,13 +25,13 @@ pub struct InitPool<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// Admin = market.authority
+    /// Admin = market.authority or risk admin
     pub authority: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
     )]
     pub market: Account<'info, Market>,
 
@@ -72,6 +74,8 @@ pub struct InitPool<'info> {
 }
 
 pub fn init_pool(ctx: Context<InitPool>, args: InitPoolArgs) -> Result<()> {
+    args.rate.validate()?;
+
     let exists = ctx
         .accounts
         .asset_registry
@@ -93,6 +97,9 @@ pub fn init_pool(ctx: Context<InitPool>, args: InitPoolArgs) -> Result<()> {
     p.total_deposit_shares_q60 = 0;
     p.last_timestamp = Clock::get()?.unix_timestamp;
     p.rate = args.rate;
+    p.supply_cap = args.supply_cap;
+    p.borrow_cap = args.borrow_cap;
+    p.accumulated_reserves_q60 = 0;
     p.bump = pool_bump;
     p.vault_auth_bump = vault_auth_bump;
 
@@ -5,11 +5,13 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::Deposit as DepositEvent,
-    state::{AssetRegistry, Market, Obligation, Pool, Position, PriceCache, RiskRegistry},
+    state::{AssetRegistry, Market, Obligation, Pool, PriceCache, PriceTable, RiskMatrix, RiskRegistry},
     utils::{
         accrual::accrue_pool,
-        health::compute_health_score_q3,
-        math::{div_u64_by_u68_to_q60, unpack_u68f60},
+        health::{compute_health_score_q3, price_for_asset_q60},
+        math::{div_ceil, div_floor, unpack_u68f60},
+        pools::load_obligation_pools,
+        realloc::grow_account,
     },
 };
 
@@ -25,16 +27,12 @@ pub struct LeverageExistingDeposit<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, market.authority.as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
-    /// CHECK: read only
-    pub authority: UncheckedAccount<'info>,
-
     #[account(
         seeds = [SEED_ASSET_REG, market.key().as_ref()],
         bump = asset_registry.bump
@@ -43,15 +41,15 @@ pub struct LeverageExistingDeposit<'info> {
 
     #[account(
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     #[account(
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Option<Account<'info, PriceCache>>,
+    pub price_cache: Option<AccountLoader<'info, PriceCache>>,
 
     #[account(
         mut,
@@ -77,36 +75,17 @@ pub struct LeverageExistingDeposit<'info> {
     pub obligation: Account<'info, Obligation>,
 
     pub system_program: Program<'info, System>,
-    // remaining_accounts: All pools for existing positions in obligation
-}
-
-#[inline]
-fn price_for_index_q60(
-    market: &Market,
-    price_cache: Option<&PriceCache>,
-    idx: u16,
-) -> Result<u128> {
-    match market.price_mode {
-        crate::state::PriceMode::Mock => Ok(U68F60::from_num(1u64).to_bits()),
-        crate::state::PriceMode::Cache => {
-            let pc = price_cache.ok_or(error!(ZodialError::PriceStale))?;
-            let found = pc
-                .prices
-                .iter()
-                .find(|p| p.asset_index == idx)
-                .ok_or(error!(ZodialError::PriceStale))?;
-            Ok(found.price_q60)
-        }
-    }
+    // remaining_accounts: All pools for existing positions in obligation (writable)
 }
 
 fn calculate_swap_output(
     market: &Market,
-    price_cache: Option<&PriceCache>,
+    price_cache: Option<&PriceTable<&[u8]>>,
     asset_registry: &AssetRegistry,
     from_mint: &Pubkey,
     to_mint: &Pubkey,
     amount: u64,
+    now: i64,
 ) -> Result<u64> {
     let from_asset = asset_registry
         .assets
@@ -120,8 +99,8 @@ fn calculate_swap_output(
         .find(|a| a.mint == *to_mint)
         .ok_or(error!(ZodialError::AssetNotRegistered))?;
 
-    let from_price_q60 = price_for_index_q60(market, price_cache, from_asset.index)?;
-    let to_price_q60 = price_for_index_q60(market, price_cache, to_asset.index)?;
+    let from_price_q60 = price_for_asset_q60(market, price_cache, from_asset, now)?;
+    let to_price_q60 = price_for_asset_q60(market, price_cache, to_asset, now)?;
 
     // Convert amount to base units, apply price conversion
     let from_base = U68F60::from_num(amount as u128)
@@ -143,29 +122,53 @@ pub fn leverage_existing_deposit(
 ) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
-    accrue_pool(&mut ctx.accounts.borrow_pool, now);
-    accrue_pool(&mut ctx.accounts.deposit_pool, now);
+    let borrow_asset = ctx
+        .accounts
+        .asset_registry
+        .assets
+        .iter()
+        .find(|a| a.mint == ctx.accounts.borrow_pool.mint)
+        .ok_or(error!(ZodialError::AssetNotRegistered))?;
+    require!(borrow_asset.enabled_for_borrow, ZodialError::BorrowDisabled);
+
+    accrue_pool(&mut ctx.accounts.borrow_pool, now)?;
+    accrue_pool(&mut ctx.accounts.deposit_pool, now)?;
+
+    let price_cache_data = ctx
+        .accounts
+        .price_cache
+        .as_ref()
+        .map(PriceCache::data)
+        .transpose()?;
+    let price_cache = price_cache_data
+        .as_deref()
+        .map(PriceTable::new)
+        .transpose()?;
 
     // Build pools list similar to borrow.rs
     let mut pools: Vec<Pool> = Vec::new();
 
     // Calculate borrow shares
     let borrow_index = unpack_u68f60(ctx.accounts.borrow_pool.borrow_fac_q60);
-    let new_borrow_shares = div_u64_by_u68_to_q60(args.borrow_amount, borrow_index)?;
+    let new_borrow_shares = div_ceil(args.borrow_amount, borrow_index)?;
 
     // Calculate swap output
     let swap_output = calculate_swap_output(
         &ctx.accounts.market,
-        ctx.accounts.price_cache.as_ref().map(|a| &**a),
+        price_cache.as_ref(),
         &ctx.accounts.asset_registry,
         &ctx.accounts.borrow_pool.mint,
         &ctx.accounts.deposit_pool.mint,
         args.borrow_amount,
+        now,
     )?;
 
+    ctx.accounts.borrow_pool.check_borrow_cap(args.borrow_amount)?;
+    ctx.accounts.deposit_pool.check_supply_cap(swap_output)?;
+
     // Calculate deposit shares from swap
     let deposit_index = unpack_u68f60(ctx.accounts.deposit_pool.deposit_fac_q60);
-    let new_deposit_shares = div_u64_by_u68_to_q60(swap_output, deposit_index)?;
+    let new_deposit_shares = div_floor(swap_output, deposit_index)?;
 
     // Build pools list with updated totals (same pattern as borrow.rs)
     {
@@ -185,121 +188,69 @@ pub fn leverage_existing_deposit(
             pools.push(touched_deposit);
         }
 
-        for ai in ctx.remaining_accounts.iter() {
-            // Only accept accounts owned by this program
-            require!(ai.owner == &crate::id(), ZodialError::Unauthorized);
-            // Deserialize Pool
-            let mut data: &[u8] = &ai.data.borrow();
-            let pool = Pool::try_deserialize(&mut data)?;
-            // Seed re-derivation for safety
-            let (expect, _b) = Pubkey::find_program_address(
-                &[
-                    SEED_POOL,
-                    ctx.accounts.market.key().as_ref(),
-                    pool.mint.as_ref(),
-                ],
-                &crate::id(),
-            );
-            require!(expect == *ai.key, ZodialError::Unauthorized);
-
-            // Skip if it's one of our touched pools
-            if pool.mint != ctx.accounts.borrow_pool.mint
-                && pool.mint != ctx.accounts.deposit_pool.mint
-            {
-                pools.push(pool);
-            }
-        }
+        // Our touched pools are accrued above and must not be passed again
+        pools.extend(load_obligation_pools(
+            ctx.remaining_accounts,
+            &ctx.accounts.market.key(),
+            &ctx.accounts.obligation,
+            &[
+                ctx.accounts.borrow_pool.mint,
+                ctx.accounts.deposit_pool.mint,
+            ],
+            now,
+        )?);
     }
 
     // Simulate changes for health check
     let mut obligation_sim = ctx.accounts.obligation.clone();
 
-    // Add borrow to simulation
-    if let Some(pos) = obligation_sim
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == ctx.accounts.borrow_pool.mint)
-    {
-        pos.borrow_shares_q60 = pos
-            .borrow_shares_q60
-            .checked_add(new_borrow_shares)
-            .ok_or(error!(ZodialError::MathOverflow))?;
-    } else {
-        obligation_sim.positions.push(Position {
-            mint: ctx.accounts.borrow_pool.mint,
-            deposit_shares_q60: 0,
-            borrow_shares_q60: new_borrow_shares,
-        });
-    }
-
-    // Add deposit to simulation
-    if let Some(pos) = obligation_sim
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == ctx.accounts.deposit_pool.mint)
-    {
-        pos.deposit_shares_q60 = pos
-            .deposit_shares_q60
-            .checked_add(new_deposit_shares)
-            .ok_or(error!(ZodialError::MathOverflow))?;
-    } else {
-        obligation_sim.positions.push(Position {
-            mint: ctx.accounts.deposit_pool.mint,
-            deposit_shares_q60: new_deposit_shares,
-            borrow_shares_q60: 0,
-        });
-    }
+    // Add borrow and deposit to simulation
+    let max_positions = ctx.accounts.market.max_positions;
+    obligation_sim.add_borrow_shares(
+        ctx.accounts.borrow_pool.mint,
+        new_borrow_shares,
+        max_positions,
+    )?;
+    obligation_sim.add_deposit_shares(
+        ctx.accounts.deposit_pool.mint,
+        new_deposit_shares,
+        max_positions,
+    )?;
 
     // Health check BEFORE applying changes (using pools like borrow.rs)
     let health = compute_health_score_q3(
         &obligation_sim,
         &ctx.accounts.market,
         &ctx.accounts.asset_registry,
-        &ctx.accounts.risk_registry,
-        ctx.accounts.price_cache.as_ref().map(|a| &**a),
+        &RiskMatrix::new(&*RiskRegistry::data(&ctx.accounts.risk_registry)?)?,
+        price_cache.as_ref(),
         &pools,
+        now,
     )?;
 
     require!(health >= 1000, ZodialError::HealthCheckFailed);
 
     // Apply actual changes
     let ob = &mut ctx.accounts.obligation;
+    ob.add_borrow_shares(
+        ctx.accounts.borrow_pool.mint,
+        new_borrow_shares,
+        max_positions,
+    )?;
+    ob.add_deposit_shares(
+        ctx.accounts.deposit_pool.mint,
+        new_deposit_shares,
+        max_positions,
+    )?;
 
-    // Add borrow position
-    if let Some(pos) = ob
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == ctx.accounts.borrow_pool.mint)
-    {
-        pos.borrow_shares_q60 = pos
-            .borrow_shares_q60
-            .checked_add(new_borrow_shares)
-            .ok_or(error!(ZodialError::MathOverflow))?;
-    } else {
-        ob.positions.push(Position {
-            mint: ctx.accounts.borrow_pool.mint,
-            deposit_shares_q60: 0,
-            borrow_shares_q60: new_borrow_shares,
-        });
-    }
-
-    // Add deposit position
-    if let Some(pos) = ob
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == ctx.accounts.deposit_pool.mint)
-    {
-        pos.deposit_shares_q60 = pos
-            .deposit_shares_q60
-            .checked_add(new_deposit_shares)
-            .ok_or(error!(ZodialError::MathOverflow))?;
-    } else {
-        ob.positions.push(Position {
-            mint: ctx.accounts.deposit_pool.mint,
-            deposit_shares_q60: new_deposit_shares,
-            borrow_shares_q60: 0,
-        });
-    }
+    // the owner pays rent for any position added above
+    let len = Obligation::space(ctx.accounts.obligation.positions.len());
+    grow_account(
+        &ctx.accounts.obligation.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        len,
+    )?;
 
     // Update pool totals
     ctx.accounts.borrow_pool.total_borrow_shares_q60 = ctx
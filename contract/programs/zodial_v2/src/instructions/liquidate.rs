@@ -4,10 +4,11 @@ use fixed::types::U68F60;
 use crate::{
     constants::*,
     error::ZodialError,
-    state::{AssetRegistry, Market, Obligation, Pool, PriceCache, RiskRegistry},
+    state::{AssetRegistry, Market, Obligation, PriceCache, PriceTable, RiskMatrix, RiskRegistry},
     utils::{
-        accrual::accrue_pool, health::compute_liquidation_health_score_q3,
-        math::div_u64_by_u68_to_q60,
+        health::{compute_liquidation_health_score_q3, price_for_asset_q60},
+        math::{div_ceil, div_floor},
+        pools::load_obligation_pools, realloc::grow_account,
     },
 };
 
@@ -24,7 +25,7 @@ pub struct CheckLiquidation<'info> {
     pub target_owner: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, market.authority.as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
@@ -38,15 +39,15 @@ pub struct CheckLiquidation<'info> {
 
     #[account(
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     #[account(
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Option<Account<'info, PriceCache>>,
+    pub price_cache: Option<AccountLoader<'info, PriceCache>>,
 
     #[account(
         mut,
@@ -58,7 +59,7 @@ pub struct CheckLiquidation<'info> {
     pub target_obligation: Account<'info, Obligation>,
 
     pub system_program: Program<'info, System>,
-    // Pool accounts for all positions in target_obligation should be passed in remaining_accounts
+    // Pool accounts for all positions in target_obligation should be passed (writable) in remaining_accounts
 }
 
 pub fn check_liquidation(
@@ -82,48 +83,41 @@ pub fn check_liquidation(
     );
 
     // Accrue interest on all pools involved
-    let mut pools: Vec<Pool> = Vec::new();
     msg!(
         "Processing {} remaining accounts (pools)...",
         ctx.remaining_accounts.len()
     );
-
-    for (i, ai) in ctx.remaining_accounts.iter().enumerate() {
-        msg!("  Pool {} - account: {}", i, ai.key);
-        // Only accept accounts owned by this program
-        require!(ai.owner == &crate::id(), ZodialError::Unauthorized);
-
-        // Deserialize Pool
-        let mut data: &[u8] = &ai.data.borrow();
-        let mut pool = Pool::try_deserialize(&mut data)?;
-
-        // Verify PDA
-        let (expect, _b) = Pubkey::find_program_address(
-            &[
-                SEED_POOL,
-                ctx.accounts.market.key().as_ref(),
-                pool.mint.as_ref(),
-            ],
-            &crate::id(),
-        );
-        require!(expect == *ai.key, ZodialError::Unauthorized);
-
+    let pools = load_obligation_pools(
+        ctx.remaining_accounts,
+        &ctx.accounts.market.key(),
+        &ctx.accounts.target_obligation,
+        &[],
+        now,
+    )?;
+    for (i, pool) in pools.iter().enumerate() {
         msg!("  Pool {} - mint: {}, verified", i, pool.mint);
-        // Accrue interest
-        accrue_pool(&mut pool, now);
-
-        pools.push(pool);
     }
 
     // Calculate liquidation health score (uses liquidation thresholds)
-    let price_cache_ref = ctx.accounts.price_cache.as_ref().map(|a| &**a);
+    let price_cache_data = ctx
+        .accounts
+        .price_cache
+        .as_ref()
+        .map(PriceCache::data)
+        .transpose()?;
+    let price_cache = price_cache_data
+        .as_deref()
+        .map(PriceTable::new)
+        .transpose()?;
+    let price_cache_ref = price_cache.as_ref();
     let health = compute_liquidation_health_score_q3(
         &ctx.accounts.target_obligation,
         &ctx.accounts.market,
         &ctx.accounts.asset_registry,
-        &ctx.accounts.risk_registry,
+        &RiskMatrix::new(&*RiskRegistry::data(&ctx.accounts.risk_registry)?)?,
         price_cache_ref,
         &pools,
+        now,
     )?;
 
     msg!("Liquidation health score (Q3): {}", health);
@@ -144,32 +138,6 @@ pub fn check_liquidation(
     Ok(())
 }
 
-// Helper function to get price by asset index (mirrors health.rs pattern)
-#[inline]
-fn price_for_index_q60(
-    market: &Market,
-    price_cache: Option<&PriceCache>,
-    idx: u16,
-) -> Result<u128> {
-    match market.price_mode {
-        crate::state::PriceMode::Mock => Ok(U68F60::from_num(1u64).to_bits()),
-        crate::state::PriceMode::Cache => {
-            let pc = price_cache.ok_or(error!(ZodialError::PriceStale))?;
-            let found = pc
-                .prices
-                .iter()
-                .find(|p| p.asset_index == idx)
-                .ok_or(error!(ZodialError::PriceStale))?;
-            Ok(found.price_q60)
-        }
-    }
-}
-
-// Helper function to convert amount to shares
-fn amount_to_shares_q60(amount: u64, index: U68F60) -> Result<u128> {
-    div_u64_by_u68_to_q60(amount, index)
-}
-
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct LiquidateObligationArgs {
     pub repay_amount: u64,
@@ -187,7 +155,7 @@ pub struct LiquidateObligation<'info> {
     pub liquidatee_owner: UncheckedAccount<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, market.authority.as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
@@ -201,15 +169,15 @@ pub struct LiquidateObligation<'info> {
 
     #[account(
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     #[account(
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Option<Account<'info, PriceCache>>,
+    pub price_cache: Option<AccountLoader<'info, PriceCache>>,
 
     #[account(
         mut,
@@ -230,7 +198,7 @@ pub struct LiquidateObligation<'info> {
     pub liquidator_obligation: Account<'info, Obligation>,
 
     pub system_program: Program<'info, System>,
-    // Pool accounts for all positions should be passed in remaining_accounts
+    // Pool accounts for all positions should be passed (writable) in remaining_accounts
 }
 
 pub fn handler_liquidate_obligation(
@@ -265,40 +233,39 @@ pub fn handler_liquidate_obligation(
         "Processing {} remaining accounts (pools)...",
         ctx.remaining_accounts.len()
     );
-    let mut pools: Vec<Pool> = Vec::new();
-    for (i, ai) in ctx.remaining_accounts.iter().enumerate() {
-        msg!("  Pool {} - account: {}", i, ai.key);
-        require!(ai.owner == &crate::id(), ZodialError::Unauthorized);
-
-        let mut data: &[u8] = &ai.data.borrow();
-        let mut pool = Pool::try_deserialize(&mut data)?;
-
-        let (expect, _b) = Pubkey::find_program_address(
-            &[
-                SEED_POOL,
-                ctx.accounts.market.key().as_ref(),
-                pool.mint.as_ref(),
-            ],
-            &crate::id(),
-        );
-        require!(expect == *ai.key, ZodialError::Unauthorized);
-
+    let pools = load_obligation_pools(
+        ctx.remaining_accounts,
+        &ctx.accounts.market.key(),
+        &ctx.accounts.liquidatee_obligation,
+        &[],
+        now,
+    )?;
+    for (i, pool) in pools.iter().enumerate() {
         msg!("  Pool {} - mint: {}, verified", i, pool.mint);
-        accrue_pool(&mut pool, now);
-        pools.push(pool);
     }
 
     msg!("Accrued interest on {} pools", pools.len());
 
     // 2. Check liquidatee is unhealthy
-    let price_cache_ref = ctx.accounts.price_cache.as_ref().map(|a| &**a);
+    let price_cache_data = ctx
+        .accounts
+        .price_cache
+        .as_ref()
+        .map(PriceCache::data)
+        .transpose()?;
+    let price_cache = price_cache_data
+        .as_deref()
+        .map(PriceTable::new)
+        .transpose()?;
+    let price_cache_ref = price_cache.as_ref();
     let health = compute_liquidation_health_score_q3(
         &ctx.accounts.liquidatee_obligation,
         &ctx.accounts.market,
         &ctx.accounts.asset_registry,
-        &ctx.accounts.risk_registry,
+        &RiskMatrix::new(&*RiskRegistry::data(&ctx.accounts.risk_registry)?)?,
         price_cache_ref,
         &pools,
+        now,
     )?;
 
     msg!("Liquidation health score (Q3): {}", health);
@@ -362,7 +329,8 @@ pub fn handler_liquidate_obligation(
         .iter()
         .find(|p| p.mint == borrow_mint)
         .ok_or(ZodialError::PoolNotFound)?;
-    let repay_shares_q60 = amount_to_shares_q60(repay_amount, borrow_pool.borrow_index())?;
+    // debt cleared and collateral seized both round down
+    let repay_shares_q60 = div_floor(repay_amount, borrow_pool.borrow_index())?;
 
     msg!("Repay shares (Q60): {}", repay_shares_q60);
     require!(
@@ -386,16 +354,28 @@ pub fn handler_liquidate_obligation(
         .iter()
         .find(|a| a.mint == collateral_mint)
         .ok_or(ZodialError::AssetNotRegistered)?;
+    // health gives these deposits no weight, so they cannot be seized either
+    require!(
+        collateral_asset.enabled_as_collateral,
+        ZodialError::CollateralDisabled
+    );
 
     // Get prices (supports Mock and Cache modes)
-    let price_cache_ref = ctx.accounts.price_cache.as_ref().map(|a| &**a);
+    let price_cache_data = ctx
+        .accounts
+        .price_cache
+        .as_ref()
+        .map(PriceCache::data)
+        .transpose()?;
+    let price_cache = price_cache_data
+        .as_deref()
+        .map(PriceTable::new)
+        .transpose()?;
+    let price_cache_ref = price_cache.as_ref();
     let borrow_price_q60 =
-        price_for_index_q60(&ctx.accounts.market, price_cache_ref, borrow_asset.index)?;
-    let collateral_price_q60 = price_for_index_q60(
-        &ctx.accounts.market,
-        price_cache_ref,
-        collateral_asset.index,
-    )?;
+        price_for_asset_q60(&ctx.accounts.market, price_cache_ref, borrow_asset, now)?;
+    let collateral_price_q60 =
+        price_for_asset_q60(&ctx.accounts.market, price_cache_ref, collateral_asset, now)?;
 
     msg!("Borrow price (Q60): {}", borrow_price_q60);
     msg!("Collateral price (Q60): {}", collateral_price_q60);
@@ -414,9 +394,7 @@ pub fn handler_liquidate_obligation(
     // The liquidator gets bonus collateral as incentive for performing the liquidation
     // seize_value = repay_value * (1 + liq_bonus_bps / 10000)
     // Example: $50 repay * 1.05 (5% bonus) = $52.50 seized
-    let risk_pair = ctx
-        .accounts
-        .risk_registry
+    let risk_pair = RiskMatrix::new(&*RiskRegistry::data(&ctx.accounts.risk_registry)?)?
         .get_pair(collateral_asset.index, borrow_asset.index)?;
 
     msg!(
@@ -448,7 +426,7 @@ pub fn handler_liquidate_obligation(
         .iter()
         .find(|p| p.mint == collateral_mint)
         .ok_or(ZodialError::PoolNotFound)?;
-    let seize_shares_q60 = amount_to_shares_q60(seize_amount, collateral_pool.deposit_index())?;
+    let seize_shares_q60 = div_floor(seize_amount, collateral_pool.deposit_index())?;
 
     msg!("Seize shares (Q60): {}", seize_shares_q60);
     require!(
@@ -459,19 +437,11 @@ pub fn handler_liquidate_obligation(
     // 5. Update liquidatee obligation (decrease borrow and collateral)
     msg!("=== Updating Liquidatee Obligation ===");
 
-    let liquidatee_borrow_pos = &mut ctx.accounts.liquidatee_obligation.positions[borrow_pos_idx];
-    liquidatee_borrow_pos.borrow_shares_q60 = liquidatee_borrow_pos
-        .borrow_shares_q60
-        .checked_sub(repay_shares_q60)
-        .ok_or(ZodialError::MathOverflow)?;
+    let liquidatee = &mut ctx.accounts.liquidatee_obligation;
+    liquidatee.remove_borrow_shares(&borrow_mint, repay_shares_q60)?;
     msg!("Liquidatee borrow decreased by {} shares", repay_shares_q60);
 
-    let liquidatee_collateral_pos =
-        &mut ctx.accounts.liquidatee_obligation.positions[collateral_pos_idx];
-    liquidatee_collateral_pos.deposit_shares_q60 = liquidatee_collateral_pos
-        .deposit_shares_q60
-        .checked_sub(seize_shares_q60)
-        .ok_or(ZodialError::MathOverflow)?;
+    liquidatee.remove_deposit_shares(&collateral_mint, seize_shares_q60)?;
     msg!(
         "Liquidatee collateral decreased by {} shares",
         seize_shares_q60
@@ -480,54 +450,51 @@ pub fn handler_liquidate_obligation(
     // 6. Update liquidator obligation (decrease deposit from repayment, increase collateral from seizure)
     msg!("=== Updating Liquidator Obligation ===");
 
-    // Find liquidator's deposit position for the borrow mint
-    // The liquidator pays with their deposits, not their borrows
-    let liquidator_deposit_pos = ctx
-        .accounts
-        .liquidator_obligation
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == borrow_mint)
-        .ok_or(ZodialError::PositionNotFound)?;
+    // The liquidator pays with their deposits of the borrow mint, not their borrows
+    let liquidator = &mut ctx.accounts.liquidator_obligation;
+    let liquidator_deposit_shares_q60 = liquidator
+        .position(&borrow_mint)
+        .ok_or(ZodialError::PositionNotFound)?
+        .deposit_shares_q60;
 
     // Convert repay_amount to deposit shares using deposit_index (NOT borrow_index)
-    // This is critical: deposit shares and borrow shares use different indices
+    // This is critical: deposit shares and borrow shares use different indices.
+    // The liquidator's payment burns shares rounded up.
     let liquidator_repay_deposit_shares_q60 =
-        amount_to_shares_q60(repay_amount, borrow_pool.deposit_index())?;
+        div_ceil(repay_amount, borrow_pool.deposit_index())?;
 
     // Ensure liquidator has sufficient deposits to cover the repayment
     require!(
-        liquidator_repay_deposit_shares_q60 <= liquidator_deposit_pos.deposit_shares_q60,
+        liquidator_repay_deposit_shares_q60 <= liquidator_deposit_shares_q60,
         ZodialError::InsufficientCollateral
     );
 
-    liquidator_deposit_pos.deposit_shares_q60 = liquidator_deposit_pos
-        .deposit_shares_q60
-        .checked_sub(liquidator_repay_deposit_shares_q60)
-        .ok_or(ZodialError::MathUnderflow)?;
+    liquidator.remove_deposit_shares(&borrow_mint, liquidator_repay_deposit_shares_q60)?;
     msg!(
         "Liquidator deposit decreased by {} shares (paying with deposits, not borrows)",
         liquidator_repay_deposit_shares_q60
     );
 
-    // Find liquidator's collateral position for the collateral mint
-    let liquidator_collateral_pos = ctx
-        .accounts
-        .liquidator_obligation
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == collateral_mint)
-        .ok_or(ZodialError::PositionNotFound)?;
-
-    liquidator_collateral_pos.deposit_shares_q60 = liquidator_collateral_pos
-        .deposit_shares_q60
-        .checked_add(seize_shares_q60)
-        .ok_or(ZodialError::MathOverflow)?;
+    // Seized collateral opens a position if the liquidator has none yet
+    liquidator.add_deposit_shares(
+        collateral_mint,
+        seize_shares_q60,
+        ctx.accounts.market.max_positions,
+    )?;
     msg!(
         "Liquidator collateral increased by {} shares",
         seize_shares_q60
     );
 
+    // the liquidator pays rent for a newly opened position
+    let len = Obligation::space(ctx.accounts.liquidator_obligation.positions.len());
+    grow_account(
+        &ctx.accounts.liquidator_obligation.to_account_info(),
+        &ctx.accounts.liquidator.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        len,
+    )?;
+
     msg!("=== Liquidation Complete ===");
     msg!("✓ Repaid {} from liquidatee's debt", repay_amount);
     msg!(
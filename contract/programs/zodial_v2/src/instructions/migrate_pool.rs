@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::PoolMigrated,
+    state::{KinkedCurve, Market, Pool, RateModel},
+    utils::realloc::resize_account,
+};
+
+/// Pool before supply/borrow caps, protocol reserves and rate curve
+/// variants; its rate model had the kinked curve's layout
+#[derive(AnchorDeserialize, InitSpace)]
+struct LegacyPool {
+    market: Pubkey,
+    mint: Pubkey,
+    vault: Pubkey,
+    borrow_fac_q60: u128,
+    deposit_fac_q60: u128,
+    total_borrow_shares_q60: u128,
+    total_deposit_shares_q60: u128,
+    last_timestamp: i64,
+    rate: KinkedCurve,
+    bump: u8,
+    vault_auth_bump: u8,
+}
+
+impl LegacyPool {
+    /// Uncapped, with no reserves and its curve as `RateModel::Kinked`
+    fn upgrade(self) -> Pool {
+        Pool {
+            market: self.market,
+            mint: self.mint,
+            vault: self.vault,
+            borrow_fac_q60: self.borrow_fac_q60,
+            deposit_fac_q60: self.deposit_fac_q60,
+            total_borrow_shares_q60: self.total_borrow_shares_q60,
+            total_deposit_shares_q60: self.total_deposit_shares_q60,
+            last_timestamp: self.last_timestamp,
+            rate: RateModel::Kinked(self.rate),
+            supply_cap: 0,
+            borrow_cap: 0,
+            accumulated_reserves_q60: 0,
+            bump: self.bump,
+            vault_auth_bump: self.vault_auth_bump,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        has_one = authority @ ZodialError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// CHECK: legacy or current Pool, layout and seeds checked in the handler
+    #[account(mut, owner = crate::ID @ ZodialError::InvalidOwner)]
+    pub pool: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrite a Pool created before caps, reserves and rate curve variants into
+/// the current layout, uncapped, with no reserves and its curve as
+/// `RateModel::Kinked`. Run after `migrate_registries` has migrated the
+/// market; pools already at the current layout are left untouched.
+pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    let ai = ctx.accounts.pool.to_account_info();
+
+    let Some(legacy) = read_legacy_pool(&ai.try_borrow_data()?)? else {
+        return Ok(());
+    };
+
+    let market = ctx.accounts.market.key();
+    require_keys_eq!(legacy.market, market, ErrorCode::ConstraintHasOne);
+    let pda = Pubkey::create_program_address(
+        &[
+            SEED_POOL,
+            market.as_ref(),
+            legacy.mint.as_ref(),
+            &[legacy.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+    require_keys_eq!(pda, ai.key(), ErrorCode::ConstraintSeeds);
+
+    let pool = legacy.upgrade();
+    resize_account(
+        &ai,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + Pool::INIT_SPACE,
+    )?;
+    pool.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+
+    emit!(PoolMigrated {
+        market,
+        mint: pool.mint,
+        pool: ai.key(),
+    });
+
+    Ok(())
+}
+
+/// The pre-caps Pool in `data`, None if it has the current layout
+fn read_legacy_pool(data: &[u8]) -> Result<Option<LegacyPool>> {
+    require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+    require!(
+        &data[..8] == Pool::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    if data.len() == 8 + Pool::INIT_SPACE {
+        return Ok(None);
+    }
+    require!(
+        data.len() == 8 + LegacyPool::INIT_SPACE,
+        ErrorCode::AccountDidNotDeserialize
+    );
+    Ok(Some(LegacyPool::deserialize(&mut &data[8..])?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(n: u8) -> Pubkey {
+        Pubkey::new_from_array([n; 32])
+    }
+
+    /// Baseline Pool: market, mint, vault, borrow and deposit factors and
+    /// shares, last timestamp, the six u16 rate fields, bump, vault auth bump
+    fn baseline_pool() -> Vec<u8> {
+        let mut data = Pool::DISCRIMINATOR.to_vec();
+        (key(1), key(2), key(3)).serialize(&mut data).unwrap();
+        (11u128, 12u128, 13u128, 14u128, 1_700_000_000i64)
+            .serialize(&mut data)
+            .unwrap();
+        (8_000u16, 100u16, 400u16, 6_000u16, 1_000u16, 9_000u16)
+            .serialize(&mut data)
+            .unwrap();
+        (252u8, 251u8).serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn pool_keeps_its_indices_and_kinked_curve() {
+        let data = baseline_pool();
+        assert_eq!(data.len(), 190);
+        let pool = read_legacy_pool(&data).unwrap().unwrap().upgrade();
+        assert_eq!(
+            (pool.market, pool.mint, pool.vault),
+            (key(1), key(2), key(3))
+        );
+        assert_eq!(
+            (
+                pool.borrow_fac_q60,
+                pool.deposit_fac_q60,
+                pool.total_borrow_shares_q60,
+                pool.total_deposit_shares_q60
+            ),
+            (11, 12, 13, 14)
+        );
+        assert_eq!(pool.last_timestamp, 1_700_000_000);
+        let RateModel::Kinked(rate) = &pool.rate else {
+            panic!("baseline curve must migrate as Kinked");
+        };
+        assert_eq!(
+            (
+                rate.kink_util_bps,
+                rate.base_borrow_apy_bps,
+                rate.slope1_bps,
+                rate.slope2_bps,
+                rate.reserve_factor_bps,
+                rate.max_borrow_apy_bps
+            ),
+            (8_000, 100, 400, 6_000, 1_000, 9_000)
+        );
+        assert_eq!((pool.supply_cap, pool.borrow_cap), (0, 0));
+        assert_eq!(pool.accumulated_reserves_q60, 0);
+        assert_eq!((pool.bump, pool.vault_auth_bump), (252, 251));
+    }
+
+    #[test]
+    fn current_or_unknown_pool_lengths() {
+        let mut current = Pool::DISCRIMINATOR.to_vec();
+        current.resize(8 + Pool::INIT_SPACE, 0);
+        assert!(read_legacy_pool(&current).unwrap().is_none());
+        let mut data = baseline_pool();
+        data.push(0);
+        assert!(read_legacy_pool(&data).is_err());
+    }
+}
@@ -0,0 +1,648 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::RegistriesMigrated,
+    state::risk_registry::pair_index,
+    state::{
+        AssetMeta, AssetRegistry, Market, MarketRoles, OracleSource, PriceCache, PriceEntry,
+        PriceMode, PriceTable, RiskPair, RiskRegistry, PRICE_SOURCE_NONE,
+    },
+    utils::realloc::resize_account,
+};
+
+/// Market before market ids and roles, seeded by [SEED_MARKET, authority]
+#[derive(AnchorDeserialize, InitSpace)]
+struct LegacyMarket {
+    authority: Pubkey,
+    max_assets: u16,
+    max_positions: u16,
+    default_ltv_bps: u16,
+    default_liq_threshold_bps: u16,
+    default_liq_bonus_bps: u16,
+    price_mode: PriceMode,
+    version: u8,
+    bump: u8,
+    price_cache_bump: u8,
+    paused: bool,
+    pyth_max_age_secs: u64,
+}
+
+impl LegacyMarket {
+    fn upgrade(self) -> Market {
+        Market {
+            authority: self.authority,
+            pending_authority: Pubkey::default(),
+            market_id: 0,
+            legacy_seed: self.authority,
+            max_assets: self.max_assets,
+            max_positions: self.max_positions,
+            default_ltv_bps: self.default_ltv_bps,
+            default_liq_threshold_bps: self.default_liq_threshold_bps,
+            default_liq_bonus_bps: self.default_liq_bonus_bps,
+            price_mode: self.price_mode,
+            version: self.version,
+            bump: self.bump,
+            price_cache_bump: self.price_cache_bump,
+            paused: self.paused,
+            pyth_max_age_secs: self.pyth_max_age_secs,
+            roles: MarketRoles::default(),
+        }
+    }
+}
+
+/// Borsh RiskRegistry, one symmetric pair per (i, j) packed in an upper
+/// triangle with the registry dim as stride
+#[derive(AnchorDeserialize)]
+struct LegacyRiskRegistry {
+    market: Pubkey,
+    bump: u8,
+    dim: u16,
+    pairs: Vec<LegacyRiskPair>,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyRiskPair {
+    ltv_bps: u16,
+    liq_threshold_bps: u16,
+    liq_bonus_bps: u16,
+}
+
+impl LegacyRiskRegistry {
+    /// Pair for (i, j) in either order, None where the registry held none
+    fn pair(&self, i: u16, j: u16) -> Option<RiskPair> {
+        if i >= self.dim || j >= self.dim {
+            return None;
+        }
+        self.pairs.get(tri_index(i, j, self.dim)).map(|p| RiskPair {
+            ltv_bps: p.ltv_bps,
+            liq_threshold_bps: p.liq_threshold_bps,
+            liq_bonus_bps: p.liq_bonus_bps,
+        })
+    }
+}
+
+/// AssetRegistry before the borrow flag and per-asset oracle settings
+#[derive(AnchorDeserialize)]
+struct LegacyAssetRegistry {
+    market: Pubkey,
+    bump: u8,
+    count: u16,
+    assets: Vec<LegacyAssetMeta>,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyAssetMeta {
+    mint: Pubkey,
+    pyth_price: Pubkey,
+    pyth_feed_id: [u8; 66],
+    decimals: u8,
+    enabled_as_collateral: bool,
+    index: u16,
+}
+
+impl LegacyAssetRegistry {
+    /// Carried assets stay borrowable, as they were, and use the market
+    /// oracle defaults
+    fn upgrade(self) -> AssetRegistry {
+        AssetRegistry {
+            market: self.market,
+            bump: self.bump,
+            count: self.count,
+            assets: self
+                .assets
+                .into_iter()
+                .map(|a| AssetMeta {
+                    mint: a.mint,
+                    pyth_price: a.pyth_price,
+                    pyth_feed_id: a.pyth_feed_id,
+                    decimals: a.decimals,
+                    enabled_as_collateral: a.enabled_as_collateral,
+                    enabled_for_borrow: true,
+                    max_price_age_secs: 0,
+                    max_conf_bps: 0,
+                    conf_multiplier_bps: 0,
+                    oracle: OracleSource::Spot,
+                    index: a.index,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Borsh PriceCache, prices listed by asset index
+#[derive(AnchorDeserialize)]
+struct LegacyPriceCache {
+    market: Pubkey,
+    bump: u8,
+    last_slot: u64,
+    prices: Vec<LegacyPriceEntry>,
+}
+
+#[derive(AnchorDeserialize)]
+struct LegacyPriceEntry {
+    asset_index: u16,
+    price_q60: u128,
+}
+
+#[derive(Accounts)]
+pub struct MigrateRegistries<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: current or pre-market-id Market, seeds and authority checked in
+    /// the handler once the layout is known
+    #[account(mut, owner = crate::ID @ ZodialError::InvalidOwner)]
+    pub market: UncheckedAccount<'info>,
+
+    /// CHECK: AssetRegistry, rewritten when the market predates MARKET_VERSION
+    #[account(
+        mut,
+        owner = crate::ID @ ZodialError::InvalidOwner,
+        seeds = [SEED_ASSET_REG, market.key().as_ref()],
+        bump
+    )]
+    pub asset_registry: UncheckedAccount<'info>,
+
+    /// CHECK: Borsh or zero-copy RiskRegistry, discriminator checked in the handler
+    #[account(
+        mut,
+        owner = crate::ID @ ZodialError::InvalidOwner,
+        seeds = [SEED_RISK_REG, market.key().as_ref()],
+        bump
+    )]
+    pub risk_registry: UncheckedAccount<'info>,
+
+    /// CHECK: Borsh or zero-copy PriceCache, discriminator checked in the handler
+    #[account(
+        mut,
+        owner = crate::ID @ ZodialError::InvalidOwner,
+        seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
+        bump
+    )]
+    pub price_cache: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Rewrite a market and its AssetRegistry, RiskRegistry and PriceCache in
+/// place into the current layouts. The Market goes first: one created before
+/// market ids is found at its authority-seeded PDA and keeps that address.
+/// Accepts the Borsh registries; accounts already migrated are left
+/// untouched. The market version is only bumped once every registry is
+/// migrated.
+pub fn migrate_registries(ctx: Context<MigrateRegistries>) -> Result<()> {
+    let authority = ctx.accounts.authority.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    let market_info = ctx.accounts.market.to_account_info();
+
+    let (mut market, market_migrated) = migrate_market(&market_info, &authority, &system_program)?;
+    let fill = market.default_risk_pair();
+
+    // AssetMeta has no discriminator of its own; markets from before
+    // MARKET_VERSION still hold the original layout
+    let (asset_registry_migrated, asset_count) = if market.version < MARKET_VERSION {
+        let count =
+            migrate_asset_registry(&ctx.accounts.asset_registry, &authority, &system_program)?;
+        (true, count)
+    } else {
+        let data = ctx.accounts.asset_registry.try_borrow_data()?;
+        (false, AssetRegistry::try_deserialize(&mut &data[..])?.count)
+    };
+    // the risk and price registries hold one row per registered asset
+    let risk_registry_migrated = migrate_risk_registry(
+        &ctx.accounts.risk_registry,
+        &authority,
+        &system_program,
+        &fill,
+        asset_count,
+    )?;
+    let price_cache_migrated = migrate_price_cache(
+        &ctx.accounts.price_cache,
+        &authority,
+        &system_program,
+        asset_count,
+    )?;
+
+    market.version = MARKET_VERSION;
+    market.try_serialize(&mut &mut market_info.try_borrow_mut_data()?[..])?;
+
+    emit!(RegistriesMigrated {
+        market: market_info.key(),
+        market_migrated,
+        asset_registry_migrated,
+        risk_registry_migrated,
+        price_cache_migrated,
+        version: MARKET_VERSION,
+    });
+
+    Ok(())
+}
+
+fn legacy_discriminator(name: &str) -> [u8; 8] {
+    let mut d = [0u8; 8];
+    d.copy_from_slice(&hash(format!("account:{}", name).as_bytes()).to_bytes()[..8]);
+    d
+}
+
+/// Load the Market, rewriting a pre-market-id layout into the current one.
+/// Checks the PDA and that `authority` is the market authority.
+fn migrate_market<'info>(
+    ai: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<(Market, bool)> {
+    let legacy = read_legacy_market(&ai.try_borrow_data()?)?;
+
+    let Some(legacy) = legacy else {
+        let market = Market::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+        let pda = Pubkey::create_program_address(
+            &[SEED_MARKET, market.pda_seed().as_ref(), &[market.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+        require_keys_eq!(pda, ai.key(), ErrorCode::ConstraintSeeds);
+        require_keys_eq!(market.authority, authority.key(), ZodialError::Unauthorized);
+        return Ok((market, false));
+    };
+
+    let pda = Pubkey::create_program_address(
+        &[SEED_MARKET, legacy.authority.as_ref(), &[legacy.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ErrorCode::ConstraintSeeds))?;
+    require_keys_eq!(pda, ai.key(), ErrorCode::ConstraintSeeds);
+    require_keys_eq!(legacy.authority, authority.key(), ZodialError::Unauthorized);
+
+    let market = legacy.upgrade();
+    resize_account(ai, authority, system_program, 8 + Market::INIT_SPACE)?;
+    market.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+
+    Ok((market, true))
+}
+
+/// The pre-market-id Market in `data`, None if it has the current layout.
+/// The discriminator did not change, the layouts differ in length.
+fn read_legacy_market(data: &[u8]) -> Result<Option<LegacyMarket>> {
+    require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+    require!(
+        &data[..8] == Market::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    if data.len() != 8 + LegacyMarket::INIT_SPACE {
+        return Ok(None);
+    }
+    Ok(Some(LegacyMarket::deserialize(&mut &data[8..])?))
+}
+
+/// Rewrite an AssetRegistry of original AssetMetas, returns the asset count
+fn migrate_asset_registry<'info>(
+    ai: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u16> {
+    let ar = read_legacy_asset_registry(&ai.try_borrow_data()?)?.upgrade();
+
+    resize_account(
+        ai,
+        payer,
+        system_program,
+        AssetRegistry::space(ar.assets.len()),
+    )?;
+    ar.try_serialize(&mut &mut ai.try_borrow_mut_data()?[..])?;
+    Ok(ar.count)
+}
+
+fn read_legacy_asset_registry(data: &[u8]) -> Result<LegacyAssetRegistry> {
+    require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+    require!(
+        &data[..8] == AssetRegistry::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(LegacyAssetRegistry::deserialize(&mut &data[8..])?)
+}
+
+/// Rewrite a Borsh RiskRegistry with one row per registered asset
+fn migrate_risk_registry<'info>(
+    ai: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    fill: &RiskPair,
+    asset_count: u16,
+) -> Result<bool> {
+    let Some(legacy) = read_legacy_risk_registry(&ai.try_borrow_data()?)? else {
+        return Ok(false);
+    };
+
+    let dim = legacy.dim.max(asset_count);
+    resize_account(ai, payer, system_program, RiskRegistry::space(dim))?;
+    write_risk_registry(&mut ai.try_borrow_mut_data()?, &legacy, dim, fill);
+
+    Ok(true)
+}
+
+/// The Borsh RiskRegistry in `data`, None if it has the current layout
+fn read_legacy_risk_registry(data: &[u8]) -> Result<Option<LegacyRiskRegistry>> {
+    require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+    if &data[..8] == RiskRegistry::DISCRIMINATOR {
+        return Ok(None);
+    }
+    require!(
+        data[..8] == legacy_discriminator("RiskRegistry"),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(Some(LegacyRiskRegistry::deserialize(&mut &data[8..])?))
+}
+
+/// Write `legacy` for `dim` assets into account data sized by
+/// `RiskRegistry::space(dim)`. A symmetric pair becomes the same terms in
+/// both directions; pairs it never held get `fill`, the defaults its health
+/// check fell back to.
+fn write_risk_registry(data: &mut [u8], legacy: &LegacyRiskRegistry, dim: u16, fill: &RiskPair) {
+    let header = RiskRegistry {
+        market: legacy.market,
+        dim,
+        bump: legacy.bump,
+        _padding: [0; 1],
+    };
+    let header_len = 8 + std::mem::size_of::<RiskRegistry>();
+    let pair_len = std::mem::size_of::<RiskPair>();
+    data[..8].copy_from_slice(RiskRegistry::DISCRIMINATOR);
+    data[8..header_len].copy_from_slice(bytemuck::bytes_of(&header));
+    for collateral in 0..dim {
+        for debt in 0..dim {
+            let pair = legacy.pair(collateral, debt).unwrap_or(*fill);
+            let at = header_len + pair_index(collateral, debt) * pair_len;
+            data[at..at + pair_len].copy_from_slice(bytemuck::bytes_of(&pair));
+        }
+    }
+}
+
+/// Slot of (i, j) in the Borsh RiskRegistry's symmetric upper triangle
+fn tri_index(i: u16, j: u16, dim: u16) -> usize {
+    let (i, j) = if i <= j {
+        (i as usize, j as usize)
+    } else {
+        (j as usize, i as usize)
+    };
+    let dim = dim as usize;
+    i * dim - (i * (i + 1)) / 2 + j
+}
+
+/// Rewrite a Borsh PriceCache with one entry per registered asset
+fn migrate_price_cache<'info>(
+    ai: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    asset_count: u16,
+) -> Result<bool> {
+    let Some(legacy) = read_legacy_price_cache(&ai.try_borrow_data()?)? else {
+        return Ok(false);
+    };
+
+    resize_account(
+        ai,
+        payer,
+        system_program,
+        PriceCache::space(asset_count as usize),
+    )?;
+    write_price_cache(&mut ai.try_borrow_mut_data()?, &legacy)?;
+
+    Ok(true)
+}
+
+/// The Borsh PriceCache in `data`, None if it has the current layout
+fn read_legacy_price_cache(data: &[u8]) -> Result<Option<LegacyPriceCache>> {
+    require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+    if &data[..8] == PriceCache::DISCRIMINATOR {
+        return Ok(None);
+    }
+    require!(
+        data[..8] == legacy_discriminator("PriceCache"),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(Some(LegacyPriceCache::deserialize(&mut &data[8..])?))
+}
+
+/// Write `legacy` into account data sized by `PriceCache::space`; prices
+/// for indices past the registered assets are dropped. No publish time is
+/// known, carried prices stay stale until refreshed.
+fn write_price_cache(data: &mut [u8], legacy: &LegacyPriceCache) -> Result<()> {
+    let header = PriceCache {
+        market: legacy.market,
+        last_slot: legacy.last_slot,
+        bump: legacy.bump,
+        _padding: [0; 7],
+    };
+    data[..8].copy_from_slice(PriceCache::DISCRIMINATOR);
+    data[8..8 + std::mem::size_of::<PriceCache>()].copy_from_slice(bytemuck::bytes_of(&header));
+    let mut pc = PriceTable::new(data)?;
+    for e in legacy.prices.iter() {
+        if (e.asset_index as usize) < pc.len() {
+            let entry = PriceEntry::new(
+                (e.price_q60, 0),
+                (e.price_q60, 0),
+                0,
+                legacy.last_slot,
+                PRICE_SOURCE_NONE,
+            );
+            pc.set_price(e.asset_index, entry)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::RiskMatrix;
+
+    fn key(n: u8) -> Pubkey {
+        Pubkey::new_from_array([n; 32])
+    }
+
+    fn pair(ltv_bps: u16, liq_threshold_bps: u16, liq_bonus_bps: u16) -> RiskPair {
+        RiskPair {
+            ltv_bps,
+            liq_threshold_bps,
+            liq_bonus_bps,
+        }
+    }
+
+    /// Baseline Market: authority, max assets and positions, default ltv,
+    /// threshold and bonus, price mode, version, bump, price cache bump,
+    /// paused, Pyth max age
+    fn baseline_market() -> Vec<u8> {
+        let mut data = Market::DISCRIMINATOR.to_vec();
+        (key(1), 33u16, 16u16, 7_000u16, 8_000u16, 500u16)
+            .serialize(&mut data)
+            .unwrap();
+        (1u8, 1u8, 254u8, 253u8, true, 60u64)
+            .serialize(&mut data)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn market_keeps_its_settings_and_authority_seed() {
+        let data = baseline_market();
+        assert_eq!(data.len(), 63);
+        let m = read_legacy_market(&data).unwrap().unwrap().upgrade();
+        assert_eq!(m.authority, key(1));
+        assert_eq!(m.legacy_seed, key(1));
+        assert_eq!(m.pda_seed(), key(1).to_bytes().to_vec());
+        assert_eq!(m.pending_authority, Pubkey::default());
+        assert_eq!((m.market_id, m.max_assets, m.max_positions), (0, 33, 16));
+        let d = m.default_risk_pair();
+        assert_eq!(
+            (d.ltv_bps, d.liq_threshold_bps, d.liq_bonus_bps),
+            (7_000, 8_000, 500)
+        );
+        assert!(matches!(m.price_mode, PriceMode::Cache));
+        assert_eq!((m.version, m.bump, m.price_cache_bump), (1, 254, 253));
+        assert!(m.paused);
+        assert_eq!(m.pyth_max_age_secs, 60);
+    }
+
+    #[test]
+    fn current_market_is_not_read_as_legacy() {
+        let market = read_legacy_market(&baseline_market())
+            .unwrap()
+            .unwrap()
+            .upgrade();
+        let mut data = Vec::new();
+        market.try_serialize(&mut data).unwrap();
+        assert!(read_legacy_market(&data).unwrap().is_none());
+        let mut foreign = baseline_market();
+        foreign[0] ^= 1;
+        assert!(read_legacy_market(&foreign).is_err());
+    }
+
+    #[test]
+    fn asset_registry_gains_default_oracle_settings() {
+        let mut data = AssetRegistry::DISCRIMINATOR.to_vec();
+        (key(1), 7u8, 2u16, 2u32).serialize(&mut data).unwrap();
+        for (i, collateral) in [(0u16, true), (1, false)] {
+            let mut feed = [0u8; 66];
+            feed[0] = b'0' + i as u8;
+            (key(10 + i as u8), key(20 + i as u8), feed, 6u8 + i as u8)
+                .serialize(&mut data)
+                .unwrap();
+            (collateral, i).serialize(&mut data).unwrap();
+        }
+
+        let ar = read_legacy_asset_registry(&data).unwrap().upgrade();
+        assert_eq!((ar.market, ar.bump, ar.count), (key(1), 7, 2));
+        assert_eq!(ar.assets.len(), 2);
+        for (i, a) in ar.assets.iter().enumerate() {
+            assert_eq!(a.mint, key(10 + i as u8));
+            assert_eq!(a.pyth_price, key(20 + i as u8));
+            assert_eq!(a.pyth_feed_id[0], b'0' + i as u8);
+            assert_eq!(a.decimals, 6 + i as u8);
+            assert_eq!(a.enabled_as_collateral, i == 0);
+            assert_eq!(a.index, i as u16);
+            assert!(a.enabled_for_borrow);
+            assert_eq!(
+                (a.max_price_age_secs, a.max_conf_bps, a.conf_multiplier_bps),
+                (0, 0, 0)
+            );
+            assert!(matches!(a.oracle, OracleSource::Spot));
+        }
+    }
+
+    /// Baseline RiskRegistry of `dim` assets, pair (i, j) = (i + j) * 100
+    /// as LTV with a 9000 threshold and a 100 bonus
+    fn baseline_risk_registry(dim: u16) -> Vec<u8> {
+        let mut pairs = Vec::new();
+        for i in 0..dim {
+            for j in i..dim {
+                pairs.push(((i + j) * 100, 9_000u16, 100u16));
+            }
+        }
+        let mut data = legacy_discriminator("RiskRegistry").to_vec();
+        (key(1), 9u8, dim, pairs).serialize(&mut data).unwrap();
+        data
+    }
+
+    fn migrate_risk(data: &[u8], asset_count: u16, fill: &RiskPair) -> Vec<u8> {
+        let legacy = read_legacy_risk_registry(data).unwrap().unwrap();
+        let dim = legacy.dim.max(asset_count);
+        let mut out = vec![0u8; RiskRegistry::space(dim)];
+        write_risk_registry(&mut out, &legacy, dim, fill);
+        out
+    }
+
+    #[test]
+    fn risk_pairs_apply_in_both_directions() {
+        let fill = pair(6_000, 7_000, 300);
+        let out = migrate_risk(&baseline_risk_registry(3), 3, &fill);
+        let m = RiskMatrix::new(&out[..]).unwrap();
+        let h = m.header();
+        assert_eq!((h.market, h.bump, h.dim), (key(1), 9, 3));
+        for c in 0..3 {
+            for d in 0..3 {
+                let p = m.get_pair(c, d).unwrap();
+                assert_eq!(
+                    (p.ltv_bps, p.liq_threshold_bps, p.liq_bonus_bps),
+                    ((c + d) * 100, 9_000, 100)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn assets_past_the_legacy_dim_get_the_defaults() {
+        let fill = pair(6_000, 7_000, 300);
+        let out = migrate_risk(&baseline_risk_registry(2), 4, &fill);
+        let m = RiskMatrix::new(&out[..]).unwrap();
+        assert_eq!(m.dim(), 4);
+        assert_eq!(m.get_pair(1, 0).unwrap().ltv_bps, 100);
+        for (c, d) in [(0, 2), (2, 0), (3, 1), (3, 3)] {
+            let p = m.get_pair(c, d).unwrap();
+            assert_eq!(
+                (p.ltv_bps, p.liq_threshold_bps, p.liq_bonus_bps),
+                (6_000, 7_000, 300)
+            );
+        }
+    }
+
+    #[test]
+    fn migrated_risk_registry_is_left_alone() {
+        let out = migrate_risk(&baseline_risk_registry(1), 1, &pair(0, 0, 0));
+        assert!(read_legacy_risk_registry(&out).unwrap().is_none());
+        assert!(read_legacy_risk_registry(&baseline_market()).is_err());
+    }
+
+    #[test]
+    fn price_cache_keeps_registered_prices_as_stale() {
+        let mut data = legacy_discriminator("PriceCache").to_vec();
+        (key(1), 8u8, 77u64).serialize(&mut data).unwrap();
+        vec![(1u16, 500u128), (0, 400), (5, 900)]
+            .serialize(&mut data)
+            .unwrap();
+
+        let legacy = read_legacy_price_cache(&data).unwrap().unwrap();
+        let mut out = vec![0u8; PriceCache::space(2)];
+        write_price_cache(&mut out, &legacy).unwrap();
+        let pc = PriceTable::new(&out[..]).unwrap();
+        let h = pc.header();
+        assert_eq!((h.market, h.bump, h.last_slot), (key(1), 8, 77));
+        assert_eq!(pc.len(), 2);
+        for (idx, price) in [(0, 400), (1, 500)] {
+            let e = pc.entry(idx).unwrap();
+            assert_eq!((e.price_q60, e.ema_price_q60), (price, price));
+            assert_eq!((e.conf_q60, e.ema_conf_q60), (0, 0));
+            assert_eq!(
+                (e.publish_time, e.slot, e.source),
+                (0, 77, PRICE_SOURCE_NONE)
+            );
+        }
+        // index 5 is past the registered assets
+        assert!(pc.entry(5).is_none());
+        assert!(pc.fresh_entry(0, 1_700_000_000, 60).is_err());
+
+        assert!(read_legacy_price_cache(&out).unwrap().is_none());
+    }
+}
@@ -1,4 +1,7 @@
+pub mod accept_authority;
 pub mod borrow;
+pub mod cancel_queued_change;
+pub mod claim_reserves;
 pub mod close_asset_registry;
 pub mod close_market;
 pub mod close_obligation;
@@ -6,22 +9,38 @@ pub mod close_pool;
 pub mod close_price_cache;
 pub mod close_risk_registry;
 pub mod deposit;
+pub mod execute_queued_change;
 pub mod faucet;
 pub mod faucet_swap;
 pub mod init_faucet_mint;
 pub mod init_market;
+pub mod init_param_queue;
 pub mod init_pool;
 pub mod leverage_existing_deposit;
 pub mod liquidate;
+pub mod migrate_pool;
+pub mod migrate_registries;
+pub mod propose_authority;
+pub mod queue_param_change;
+pub mod refresh_pools;
 pub mod register_asset;
 pub mod repay;
+pub mod set_market_roles;
 pub mod set_risk_pair;
 pub mod set_risk_pairs_batch;
+pub mod update_asset_flags;
+pub mod update_market_config;
+pub mod update_pool_caps;
+pub mod update_pool_rate_model;
 pub mod update_prices;
 pub mod update_prices_pyth;
+pub mod update_prices_pyth_batch;
 pub mod withdraw;
 
+pub use accept_authority::*;
 pub use borrow::*;
+pub use cancel_queued_change::*;
+pub use claim_reserves::*;
 pub use close_asset_registry::*;
 pub use close_market::*;
 pub use close_obligation::*;
@@ -29,17 +48,30 @@ pub use close_pool::*;
 pub use close_price_cache::*;
 pub use close_risk_registry::*;
 pub use deposit::*;
+pub use execute_queued_change::*;
 pub use faucet::*;
 pub use faucet_swap::*;
 pub use init_faucet_mint::*;
 pub use init_market::*;
+pub use init_param_queue::*;
 pub use init_pool::*;
 pub use leverage_existing_deposit::*;
 pub use liquidate::*;
+pub use migrate_pool::*;
+pub use migrate_registries::*;
+pub use propose_authority::*;
+pub use queue_param_change::*;
+pub use refresh_pools::*;
 pub use register_asset::*;
 pub use repay::*;
+pub use set_market_roles::*;
 pub use set_risk_pair::*;
 pub use set_risk_pairs_batch::*;
+pub use update_asset_flags::*;
+pub use update_market_config::*;
+pub use update_pool_caps::*;
+pub use update_pool_rate_model::*;
 pub use update_prices::*;
 pub use update_prices_pyth::*;
+pub use update_prices_pyth_batch::*;
 pub use withdraw::*;
\ No newline at end of file
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, error::ZodialError, events::AuthorityProposed, state::Market};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProposeAuthorityArgs {
+    /// Pubkey::default() cancels a pending handover
+    pub new_authority: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ProposeAuthorityArgs)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        has_one = authority @ ZodialError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+/// Step 1 of the handover: current authority nominates a successor.
+/// Nothing changes until the successor signs `accept_authority`.
+pub fn propose_authority(ctx: Context<ProposeAuthority>, args: ProposeAuthorityArgs) -> Result<()> {
+    let m = &mut ctx.accounts.market;
+    m.pending_authority = args.new_authority;
+
+    emit!(AuthorityProposed {
+        market: m.key(),
+        authority: m.authority,
+        pending_authority: args.new_authority,
+    });
+
+    Ok(())
+}
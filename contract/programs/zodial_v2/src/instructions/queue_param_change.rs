@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::ParamChangeQueued,
+    state::{
+        risk_registry::check_pair_indices, AssetRegistry, Market, ParamChange, ParamChangeQueue,
+        QueuedChange, RiskPair, Role,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QueueParamChangeArgs {
+    pub change: ParamChange,
+}
+
+#[derive(Accounts)]
+#[instruction(args: QueueParamChangeArgs)]
+pub struct QueueParamChange<'info> {
+    /// Market authority or risk admin; only the authority may queue `Delay`
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [SEED_ASSET_REG, market.key().as_ref()],
+        bump = asset_registry.bump
+    )]
+    pub asset_registry: Account<'info, AssetRegistry>,
+
+    #[account(
+        mut,
+        seeds = [SEED_PARAM_QUEUE, market.key().as_ref()],
+        bump = param_queue.bump
+    )]
+    pub param_queue: Account<'info, ParamChangeQueue>,
+}
+
+pub fn queue_param_change(
+    ctx: Context<QueueParamChange>,
+    args: QueueParamChangeArgs,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let q = &mut ctx.accounts.param_queue;
+
+    require!(
+        q.changes.len() < MAX_QUEUED_CHANGES,
+        ZodialError::ParamQueueFull
+    );
+    match &args.change {
+        ParamChange::PoolRateModel { rate, .. } => rate.validate()?,
+        ParamChange::Delay { delay_secs } => {
+            // the risk role may not shorten the timelock it is bound by
+            require_keys_eq!(
+                ctx.accounts.authority.key(),
+                ctx.accounts.market.authority,
+                ZodialError::Unauthorized
+            );
+            require!(
+                *delay_secs >= MIN_PARAM_DELAY_SECS,
+                ZodialError::InvalidMarketConfig
+            )
+        }
+        ParamChange::RiskPair {
+            a_index,
+            b_index,
+            ltv_bps,
+            liq_threshold_bps,
+            liq_bonus_bps,
+        } => {
+            // reject bad indices before the delay starts, not at execute
+            check_pair_indices(*a_index, *b_index, ctx.accounts.asset_registry.count)?;
+            RiskPair {
+                ltv_bps: *ltv_bps,
+                liq_threshold_bps: *liq_threshold_bps,
+                liq_bonus_bps: *liq_bonus_bps,
+            }
+            .validate()?
+        }
+    }
+
+    let id = q.next_id;
+    let eta = now
+        .checked_add(q.delay_secs)
+        .ok_or(error!(ZodialError::MathOverflow))?;
+    q.next_id = q
+        .next_id
+        .checked_add(1)
+        .ok_or(error!(ZodialError::MathOverflow))?;
+    q.changes.push(QueuedChange {
+        id,
+        eta,
+        change: args.change.clone(),
+    });
+
+    emit!(ParamChangeQueued {
+        market: ctx.accounts.market.key(),
+        id,
+        eta,
+        change: args.change,
+    });
+
+    Ok(())
+}
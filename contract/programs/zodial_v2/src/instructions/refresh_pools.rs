@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, events::PoolAccrued, state::Market, utils::pools::load_accrued_pools};
+
+#[derive(Accounts)]
+pub struct RefreshPools<'info> {
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+    // remaining_accounts: writable Pool PDAs of this market
+}
+
+/// Permissionless crank: accrue and persist any number of pools.
+pub fn refresh_pools(ctx: Context<RefreshPools>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let market_key = ctx.accounts.market.key();
+
+    let pools = load_accrued_pools(ctx.remaining_accounts, &market_key, now)?;
+
+    for (ai, pool) in ctx.remaining_accounts.iter().zip(pools.iter()) {
+        emit!(PoolAccrued {
+            market: market_key,
+            mint: pool.mint,
+            pool: *ai.key,
+            borrow_fac_q60: pool.borrow_fac_q60,
+            deposit_fac_q60: pool.deposit_fac_q60,
+            accumulated_reserves_q60: pool.accumulated_reserves_q60,
+            utilization_bps: pool.utilization_bps(),
+            timestamp: now,
+        });
+    }
+
+    Ok(())
+}
@@ -2,7 +2,11 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::AssetRegistered,
-    state::{AssetMeta, AssetRegistry, Market, RiskRegistry},
+    state::{
+        AssetMeta, AssetRegistry, Market, OracleSource, PriceCache, RiskMatrix, RiskRegistry,
+        Role,
+    },
+    utils::realloc::grow_account,
 };
 use anchor_lang::prelude::*;
 
@@ -13,17 +17,24 @@ pub struct RegisterAssetArgs {
     pub pyth_price: Pubkey, // optional; or Pubkey::default()
     pub pyth_feed_id: Option<[u8; 66]>, // Optional Pyth Pull oracle feed ID
     pub enabled_as_collateral: bool,
+    pub enabled_for_borrow: bool,
+    pub max_price_age_secs: u32, // 0 = market default
+    pub max_conf_bps: u16,       // 0 = unchecked
+    pub conf_multiplier_bps: u16,
+    pub oracle: OracleSource,
 }
 
 #[derive(Accounts)]
 pub struct RegisterAsset<'info> {
+    /// Market authority or risk admin, pays for registry growth
+    #[account(mut)]
     pub authority: Signer<'info>,
 
     #[account(
         mut,
-        has_one = authority,
-        seeds = [SEED_MARKET, authority.key().as_ref()],
-        bump = market.bump
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
     )]
     pub market: Account<'info, Market>,
 
@@ -37,35 +48,72 @@ pub struct RegisterAsset<'info> {
     #[account(
         mut,
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
+
+    #[account(
+        mut,
+        seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
+        bump = market.price_cache_bump
+    )]
+    pub price_cache: AccountLoader<'info, PriceCache>,
 
     pub system_program: Program<'info, System>,
 }
 
 pub fn register_asset(ctx: Context<RegisterAsset>, args: RegisterAssetArgs) -> Result<()> {
     let ar = &mut ctx.accounts.asset_registry;
-    let rr = &mut ctx.accounts.risk_registry;
     let mkt = &ctx.accounts.market;
 
     require!(ar.count < mkt.max_assets, ZodialError::ExceedsMaxAssets);
 
     let index = ar.count;
-    ar.assets.push(AssetMeta {
+    let asset = AssetMeta {
         mint: args.mint,
         pyth_price: args.pyth_price,
         pyth_feed_id: args.pyth_feed_id.unwrap_or([0u8; 66]),
         decimals: args.decimals,
         enabled_as_collateral: args.enabled_as_collateral,
+        enabled_for_borrow: args.enabled_for_borrow,
+        max_price_age_secs: args.max_price_age_secs,
+        max_conf_bps: args.max_conf_bps,
+        conf_multiplier_bps: args.conf_multiplier_bps,
+        oracle: args.oracle,
         index,
-    });
+    };
+    asset.validate_conf()?;
+    ar.assets.push(asset);
     ar.count = ar
         .count
         .checked_add(1)
         .ok_or(error!(ZodialError::MathOverflow))?;
 
-    rr.dim = ar.count;
+    let count = ar.count;
+    let asset_registry_len = AssetRegistry::space(ar.assets.len());
+    let fill = mkt.default_risk_pair();
+    let payer = ctx.accounts.authority.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+    for (account, len) in [
+        (
+            ctx.accounts.asset_registry.to_account_info(),
+            asset_registry_len,
+        ),
+        (
+            ctx.accounts.risk_registry.to_account_info(),
+            RiskRegistry::space(count),
+        ),
+        (
+            ctx.accounts.price_cache.to_account_info(),
+            PriceCache::space(count as usize),
+        ),
+    ] {
+        grow_account(&account, &payer, &system_program, len)?;
+    }
+
+    // the new asset's pairs start at the market defaults
+    let mut rr_data = RiskRegistry::data_mut(&ctx.accounts.risk_registry)?;
+    RiskMatrix::new(&mut *rr_data)?.resize(count, &fill)?;
 
     emit!(AssetRegistered {
         market: mkt.key(),
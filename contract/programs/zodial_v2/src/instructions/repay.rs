@@ -7,7 +7,7 @@ use crate::{
     events::Repay as RepayEvent,
     state::{AssetRegistry, Market, Obligation, Pool},
     utils::accrual::accrue_pool,
-    utils::math::{div_u64_by_u68_to_q60, mul_q60_by_u68_to_u64, unpack_u68f60},
+    utils::math::{div_floor, mul_ceil, unpack_u68f60},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -22,16 +22,12 @@ pub struct Repay<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
-    /// CHECK: read-only
-    pub authority: UncheckedAccount<'info>,
-
     #[account(
         seeds = [SEED_ASSET_REG, market.key().as_ref()],
         bump = asset_registry.bump
@@ -82,21 +78,21 @@ pub struct Repay<'info> {
 pub fn repay(ctx: Context<Repay>, args: RepayArgs) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
-    accrue_pool(&mut ctx.accounts.pool, now);
+    accrue_pool(&mut ctx.accounts.pool, now)?;
 
     let pool_mint = ctx.accounts.pool.mint;
-    let ob = &mut ctx.accounts.obligation;
-    let pos_idx = ob
-        .positions
-        .iter()
-        .position(|p| p.mint == pool_mint)
-        .ok_or(error!(ZodialError::PoolNotFound))?;
-    let pos = &mut ob.positions[pos_idx];
+    let borrow_shares_q60 = ctx
+        .accounts
+        .obligation
+        .position(&pool_mint)
+        .ok_or(error!(ZodialError::PoolNotFound))?
+        .borrow_shares_q60;
 
-    require!(pos.borrow_shares_q60 > 0, ZodialError::MathOverflow); // use a dedicated error if you prefer
+    require!(borrow_shares_q60 > 0, ZodialError::MathOverflow); // use a dedicated error if you prefer
 
     let borrow_idx = unpack_u68f60(ctx.accounts.pool.borrow_fac_q60);
-    let debt_underlying = mul_q60_by_u68_to_u64(pos.borrow_shares_q60, borrow_idx)?; // u64
+    // the amount owed rounds up, the shares a payment burns round down
+    let debt_underlying = mul_ceil(borrow_shares_q60, borrow_idx)?; // u64
 
     if debt_underlying == 0 {
         return Ok(());
@@ -107,13 +103,15 @@ pub fn repay(ctx: Context<Repay>, args: RepayArgs) -> Result<()> {
         return Ok(());
     }
 
-    let shares_to_burn_q60 = div_u64_by_u68_to_q60(repay_amount, borrow_idx)?;
-
-    let burn_q60 = shares_to_burn_q60.min(pos.borrow_shares_q60);
-    pos.borrow_shares_q60 = pos
-        .borrow_shares_q60
-        .checked_sub(burn_q60)
-        .ok_or(error!(ZodialError::MathOverflow))?;
+    // paying the full rounded-up debt clears the position
+    let burn_q60 = if repay_amount == debt_underlying {
+        borrow_shares_q60
+    } else {
+        div_floor(repay_amount, borrow_idx)?.min(borrow_shares_q60)
+    };
+    ctx.accounts
+        .obligation
+        .remove_borrow_shares(&pool_mint, burn_q60)?;
 
     {
         let p = &mut ctx.accounts.pool;
@@ -136,11 +134,6 @@ pub fn repay(ctx: Context<Repay>, args: RepayArgs) -> Result<()> {
         token::transfer(cpi, repay_amount)?;
     }
 
-    // drop empty positions
-    if pos.borrow_shares_q60 == 0 && pos.deposit_shares_q60 == 0 {
-        ob.positions.swap_remove(pos_idx);
-    }
-
     emit!(RepayEvent {
         market: ctx.accounts.market.key(),
         owner: ctx.accounts.owner.key(),
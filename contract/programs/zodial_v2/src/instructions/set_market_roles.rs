@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::*, error::ZodialError, events::MarketRolesUpdated, state::Market};
+
+/// `None` keeps the current holder, `Some(Pubkey::default())` revokes the role.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SetMarketRolesArgs {
+    pub risk_admin: Option<Pubkey>,
+    pub oracle_admin: Option<Pubkey>,
+    pub pause_guardian: Option<Pubkey>,
+    pub treasury: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: SetMarketRolesArgs)]
+pub struct SetMarketRoles<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        has_one = authority @ ZodialError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn set_market_roles(ctx: Context<SetMarketRoles>, args: SetMarketRolesArgs) -> Result<()> {
+    let m = &mut ctx.accounts.market;
+
+    if let Some(k) = args.risk_admin {
+        m.roles.risk_admin = k;
+    }
+    if let Some(k) = args.oracle_admin {
+        m.roles.oracle_admin = k;
+    }
+    if let Some(k) = args.pause_guardian {
+        m.roles.pause_guardian = k;
+    }
+    if let Some(k) = args.treasury {
+        m.roles.treasury = k;
+    }
+
+    emit!(MarketRolesUpdated {
+        market: m.key(),
+        risk_admin: m.roles.risk_admin,
+        oracle_admin: m.roles.oracle_admin,
+        pause_guardian: m.roles.pause_guardian,
+        treasury: m.roles.treasury,
+    });
+
+    Ok(())
+}
@@ -4,13 +4,14 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::RiskPairSet,
-    state::risk_registry::tri_index,
-    state::{AssetRegistry, Market, RiskPair, RiskRegistry},
+    state::{AssetRegistry, Market, RiskMatrix, RiskPair, RiskRegistry, Role},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct SetRiskPairArgs {
+    /// Collateral asset
     pub a_mint: Pubkey,
+    /// Debt asset; the pair only applies in this direction
     pub b_mint: Pubkey,
     pub ltv_bps: u16,
     pub liq_threshold_bps: u16,
@@ -19,13 +20,14 @@ pub struct SetRiskPairArgs {
 
 #[derive(Accounts)]
 pub struct SetRiskPair<'info> {
+    /// Market authority or risk admin
     pub authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
     )]
     pub market: Account<'info, Market>,
 
@@ -38,14 +40,17 @@ pub struct SetRiskPair<'info> {
     #[account(
         mut,
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 }
 
+/// Instant write, limited to loosening the pair in force; tightening goes
+/// through queue_param_change
 pub fn set(ctx: Context<SetRiskPair>, args: SetRiskPairArgs) -> Result<()> {
     let ar = &ctx.accounts.asset_registry;
-    let rr = &mut ctx.accounts.risk_registry;
+    let mut rr_data = RiskRegistry::data_mut(&ctx.accounts.risk_registry)?;
+    let mut rr = RiskMatrix::new(&mut *rr_data)?;
     let mkt = &ctx.accounts.market;
 
     // map mints -> indices
@@ -62,31 +67,18 @@ pub fn set(ctx: Context<SetRiskPair>, args: SetRiskPairArgs) -> Result<()> {
         .map(|a| a.index)
         .ok_or(error!(ZodialError::AssetNotRegistered))?;
 
-    // ensure dim matches registry count
-    let dim = ar.count;
-    if rr.dim != dim {
-        rr.dim = dim;
-    }
+    let default = mkt.default_risk_pair();
 
-    // expand triangular storage
-    let needed = (dim as usize * (dim as usize + 1)) / 2;
-    if rr.pairs.len() < needed {
-        let fill = RiskPair {
-            ltv_bps: mkt.default_ltv_bps,
-            liq_threshold_bps: mkt.default_liq_threshold_bps,
-            liq_bonus_bps: mkt.default_liq_bonus_bps,
-        };
-        while rr.pairs.len() < needed {
-            rr.pairs.push(fill.clone());
-        }
-    }
-
-    let k = tri_index(ai, bi, dim);
-    rr.pairs[k] = RiskPair {
-        ltv_bps: args.ltv_bps,
-        liq_threshold_bps: args.liq_threshold_bps,
-        liq_bonus_bps: args.liq_bonus_bps,  
-    };
+    rr.loosen_pair(
+        ai,
+        bi,
+        RiskPair {
+            ltv_bps: args.ltv_bps,
+            liq_threshold_bps: args.liq_threshold_bps,
+            liq_bonus_bps: args.liq_bonus_bps,
+        },
+        &default,
+    )?;
 
     emit!(RiskPairSet {
         market: mkt.key(),
@@ -4,13 +4,14 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::RiskPairsBatchSet,
-    state::risk_registry::tri_index,
-    state::{AssetRegistry, Market, RiskPair, RiskRegistry},
+    state::{AssetRegistry, Market, RiskMatrix, RiskPair, RiskRegistry, Role},
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct RiskPairEntry {
+    /// Collateral asset index
     pub a_index: u16,
+    /// Debt asset index
     pub b_index: u16,
     pub ltv_bps: u16,
     pub liq_threshold_bps: u16,
@@ -24,13 +25,14 @@ pub struct SetRiskPairsBatchArgs {
 
 #[derive(Accounts)]
 pub struct SetRiskPairsBatch<'info> {
+    /// Market authority or risk admin
     pub authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
     )]
     pub market: Account<'info, Market>,
 
@@ -43,52 +45,37 @@ pub struct SetRiskPairsBatch<'info> {
     #[account(
         mut,
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 }
 
+/// Instant writes for many pairs, each limited to loosening the pair in
+/// force; tightening goes through queue_param_change
 pub fn set_batch(
     ctx: Context<SetRiskPairsBatch>,
     args: SetRiskPairsBatchArgs,
 ) -> Result<()> {
-    let ar = &ctx.accounts.asset_registry;
-    let rr = &mut ctx.accounts.risk_registry;
+    let mut rr_data = RiskRegistry::data_mut(&ctx.accounts.risk_registry)?;
+    let mut rr = RiskMatrix::new(&mut *rr_data)?;
     let mkt = &ctx.accounts.market;
 
-    // Ensure dim matches registry count
-    let dim = ar.count;
-    if rr.dim != dim {
-        rr.dim = dim;
-    }
-
-    // Expand triangular storage if needed
-    let needed = (dim as usize * (dim as usize + 1)) / 2;
-    if rr.pairs.len() < needed {
-        let fill = RiskPair {
-            ltv_bps: mkt.default_ltv_bps,
-            liq_threshold_bps: mkt.default_liq_threshold_bps,
-            liq_bonus_bps: mkt.default_liq_bonus_bps,
-        };
-        while rr.pairs.len() < needed {
-            rr.pairs.push(fill.clone());
-        }
-    }
-
-    // Validate and set all pairs in batch
-    for entry in args.pairs.iter() {
-        // Validate indices are within bounds
-        require!(
-            entry.a_index < dim && entry.b_index < dim,
-            ZodialError::AssetNotRegistered
-        );
+    let default = mkt.default_risk_pair();
 
-        let k = tri_index(entry.a_index, entry.b_index, dim);
-        rr.pairs[k] = RiskPair {
-            ltv_bps: entry.ltv_bps,
-            liq_threshold_bps: entry.liq_threshold_bps,
-            liq_bonus_bps: entry.liq_bonus_bps,
-        };
+    // loosen_pair validates indices and invariants and rejects tightening,
+    // one bad entry fails the batch
+    for (n, entry) in args.pairs.iter().enumerate() {
+        rr.loosen_pair(
+            entry.a_index,
+            entry.b_index,
+            RiskPair {
+                ltv_bps: entry.ltv_bps,
+                liq_threshold_bps: entry.liq_threshold_bps,
+                liq_bonus_bps: entry.liq_bonus_bps,
+            },
+            &default,
+        )
+        .inspect_err(|_| msg!("Rejected risk pair entry {}", n))?;
     }
 
     emit!(RiskPairsBatchSet {
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::AssetFlagsUpdated,
+    state::{AssetRegistry, Market, OracleSource, Role},
+};
+
+/// `None` keeps the current flag.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateAssetFlagsArgs {
+    pub mint: Pubkey,
+    pub enabled_as_collateral: Option<bool>,
+    pub enabled_for_borrow: Option<bool>,
+    pub max_price_age_secs: Option<u32>,
+    pub max_conf_bps: Option<u16>,
+    pub conf_multiplier_bps: Option<u16>,
+    pub oracle: Option<OracleSource>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdateAssetFlagsArgs)]
+pub struct UpdateAssetFlags<'info> {
+    /// Market authority or risk admin; only the authority may reduce
+    /// collateral value, see `update_asset_flags`
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [SEED_ASSET_REG, market.key().as_ref()],
+        bump = asset_registry.bump
+    )]
+    pub asset_registry: Account<'info, AssetRegistry>,
+}
+
+/// Disabling collateral, widening the health band or switching the oracle
+/// source can make accounts liquidatable at once, so the risk role may only
+/// change these in the holder's favour; the authority may change them freely.
+pub fn update_asset_flags(
+    ctx: Context<UpdateAssetFlags>,
+    args: UpdateAssetFlagsArgs,
+) -> Result<()> {
+    let is_authority = ctx.accounts.authority.key() == ctx.accounts.market.authority;
+    let asset = ctx
+        .accounts
+        .asset_registry
+        .assets
+        .iter_mut()
+        .find(|a| a.mint == args.mint)
+        .ok_or(error!(ZodialError::AssetNotRegistered))?;
+
+    let reduces_collateral = (args.enabled_as_collateral == Some(false)
+        && asset.enabled_as_collateral)
+        || args
+            .conf_multiplier_bps
+            .is_some_and(|k| k > asset.conf_multiplier_bps)
+        || args.oracle.is_some_and(|o| o != asset.oracle);
+    require!(
+        !reduces_collateral || is_authority,
+        ZodialError::Unauthorized
+    );
+
+    if let Some(v) = args.enabled_as_collateral {
+        asset.enabled_as_collateral = v;
+    }
+    if let Some(v) = args.enabled_for_borrow {
+        asset.enabled_for_borrow = v;
+    }
+    if let Some(v) = args.max_price_age_secs {
+        asset.max_price_age_secs = v;
+    }
+    if let Some(v) = args.max_conf_bps {
+        asset.max_conf_bps = v;
+    }
+    if let Some(v) = args.conf_multiplier_bps {
+        asset.conf_multiplier_bps = v;
+    }
+    if let Some(v) = args.oracle {
+        asset.oracle = v;
+    }
+    asset.validate_conf()?;
+
+    emit!(AssetFlagsUpdated {
+        market: ctx.accounts.market.key(),
+        mint: args.mint,
+        enabled_as_collateral: asset.enabled_as_collateral,
+        enabled_for_borrow: asset.enabled_for_borrow,
+        max_price_age_secs: asset.max_price_age_secs,
+        max_conf_bps: asset.max_conf_bps,
+        conf_multiplier_bps: asset.conf_multiplier_bps,
+        oracle: asset.oracle,
+    });
+
+    Ok(())
+}
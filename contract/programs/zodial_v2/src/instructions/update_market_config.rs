@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::MarketConfigUpdated,
+    state::{Market, RiskPair, Role},
+};
+
+/// Every field is optional; `None` keeps the current value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateMarketConfigArgs {
+    pub paused: Option<bool>,
+    pub max_positions: Option<u16>,
+    pub default_ltv_bps: Option<u16>,
+    pub default_liq_threshold_bps: Option<u16>,
+    pub default_liq_bonus_bps: Option<u16>,
+    pub pyth_max_age_secs: Option<u64>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdateMarketConfigArgs)]
+pub struct UpdateMarketConfig<'info> {
+    /// Authority, risk admin or pause guardian; checked per field in the handler
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+}
+
+pub fn update_market_config(
+    ctx: Context<UpdateMarketConfig>,
+    args: UpdateMarketConfigArgs,
+) -> Result<()> {
+    let signer = ctx.accounts.authority.key();
+    let m = &mut ctx.accounts.market;
+
+    // guardian may pause, only the authority may unpause
+    match args.paused {
+        Some(true) => require!(
+            m.has_role(Role::PauseGuardian, &signer),
+            ZodialError::MissingRole
+        ),
+        Some(false) => require_keys_eq!(signer, m.authority, ZodialError::Unauthorized),
+        None => {}
+    }
+    if args.default_ltv_bps.is_some()
+        || args.default_liq_threshold_bps.is_some()
+        || args.default_liq_bonus_bps.is_some()
+    {
+        require!(m.has_role(Role::Risk, &signer), ZodialError::MissingRole);
+    }
+    if args.max_positions.is_some() || args.pyth_max_age_secs.is_some() {
+        require_keys_eq!(signer, m.authority, ZodialError::Unauthorized);
+    }
+
+    let paused = args.paused.unwrap_or(m.paused);
+    let max_positions = args.max_positions.unwrap_or(m.max_positions);
+    let ltv = args.default_ltv_bps.unwrap_or(m.default_ltv_bps);
+    let threshold = args
+        .default_liq_threshold_bps
+        .unwrap_or(m.default_liq_threshold_bps);
+    let bonus = args
+        .default_liq_bonus_bps
+        .unwrap_or(m.default_liq_bonus_bps);
+    let pyth_max_age_secs = args.pyth_max_age_secs.unwrap_or(m.pyth_max_age_secs);
+
+    // only validate what is being changed, so pausing never fails on legacy values
+    if args.max_positions.is_some() {
+        require!(
+            max_positions > 0 && max_positions as usize <= MAX_POSITIONS,
+            ZodialError::ExceedsMaxPositions
+        );
+    }
+    if args.default_ltv_bps.is_some()
+        || args.default_liq_threshold_bps.is_some()
+        || args.default_liq_bonus_bps.is_some()
+    {
+        RiskPair {
+            ltv_bps: ltv,
+            liq_threshold_bps: threshold,
+            liq_bonus_bps: bonus,
+        }
+        .validate()?;
+        require!(bonus <= MAX_LIQ_BONUS_BPS, ZodialError::InvalidMarketConfig);
+    }
+    if args.pyth_max_age_secs.is_some() {
+        require!(pyth_max_age_secs > 0, ZodialError::InvalidMarketConfig);
+    }
+
+    let event = MarketConfigUpdated {
+        market: m.key(),
+        old_paused: m.paused,
+        new_paused: paused,
+        old_max_positions: m.max_positions,
+        new_max_positions: max_positions,
+        old_default_ltv_bps: m.default_ltv_bps,
+        new_default_ltv_bps: ltv,
+        old_default_liq_threshold_bps: m.default_liq_threshold_bps,
+        new_default_liq_threshold_bps: threshold,
+        old_default_liq_bonus_bps: m.default_liq_bonus_bps,
+        new_default_liq_bonus_bps: bonus,
+        old_pyth_max_age_secs: m.pyth_max_age_secs,
+        new_pyth_max_age_secs: pyth_max_age_secs,
+    };
+
+    m.paused = paused;
+    m.max_positions = max_positions;
+    m.default_ltv_bps = ltv;
+    m.default_liq_threshold_bps = threshold;
+    m.default_liq_bonus_bps = bonus;
+    m.pyth_max_age_secs = pyth_max_age_secs;
+
+    emit!(event);
+
+    Ok(())
+}
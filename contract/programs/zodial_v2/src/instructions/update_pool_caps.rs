@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::PoolCapsUpdated,
+    state::{Market, Pool, Role},
+};
+
+/// Caps are in underlying atoms, 0 = uncapped. `None` keeps the current cap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdatePoolCapsArgs {
+    pub supply_cap: Option<u64>,
+    pub borrow_cap: Option<u64>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdatePoolCapsArgs)]
+pub struct UpdatePoolCaps<'info> {
+    /// Market authority or risk admin
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        constraint = market.has_role(Role::Risk, &authority.key()) @ ZodialError::MissingRole
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        has_one = market,
+        seeds = [SEED_POOL, market.key().as_ref(), pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Lowering a cap below current totals only blocks new deposits/borrows.
+pub fn update_pool_caps(ctx: Context<UpdatePoolCaps>, args: UpdatePoolCapsArgs) -> Result<()> {
+    let p = &mut ctx.accounts.pool;
+
+    if let Some(cap) = args.supply_cap {
+        p.supply_cap = cap;
+    }
+    if let Some(cap) = args.borrow_cap {
+        p.borrow_cap = cap;
+    }
+
+    emit!(PoolCapsUpdated {
+        market: ctx.accounts.market.key(),
+        mint: p.mint,
+        supply_cap: p.supply_cap,
+        borrow_cap: p.borrow_cap,
+    });
+
+    Ok(())
+}
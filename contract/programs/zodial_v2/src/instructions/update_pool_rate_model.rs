@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::PoolRateModelUpdated,
+    state::{Market, Pool, RateModel},
+    utils::accrual::accrue_pool,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdatePoolRateModelArgs {
+    pub rate: RateModel,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdatePoolRateModelArgs)]
+pub struct UpdatePoolRateModel<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump,
+        has_one = authority @ ZodialError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        has_one = market,
+        seeds = [SEED_POOL, market.key().as_ref(), pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Authority-only instant curve change; the risk role queues
+/// `ParamChange::PoolRateModel` instead
+pub fn update_pool_rate_model(
+    ctx: Context<UpdatePoolRateModel>,
+    args: UpdatePoolRateModelArgs,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let p = &mut ctx.accounts.pool;
+
+    // settle past interest at the old curve
+    accrue_pool(p, now)?;
+
+    args.rate.validate()?;
+    let old_rate = std::mem::replace(&mut p.rate, args.rate.clone());
+
+    emit!(PoolRateModelUpdated {
+        market: ctx.accounts.market.key(),
+        mint: p.mint,
+        pool: p.key(),
+        old_rate,
+        new_rate: args.rate,
+    });
+
+    Ok(())
+}
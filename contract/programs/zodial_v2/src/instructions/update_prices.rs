@@ -4,7 +4,10 @@ use crate::{
     constants::*,
     error::ZodialError,
     events::PricesUpdated,
-    state::{AssetRegistry, Market, PriceCache, PriceEntry, PriceMode},
+    state::{
+        AssetRegistry, Market, PriceCache, PriceEntry, PriceMode, PriceTable, Role,
+        PRICE_SOURCE_MANUAL,
+    },
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -21,13 +24,14 @@ pub struct UpdatePricesArgs {
 #[derive(Accounts)]
 #[instruction(args: UpdatePricesArgs)]
 pub struct UpdatePrices<'info> {
+    /// Market authority or oracle admin
     pub authority: Signer<'info>,
 
     #[account(
         mut,
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority
+        constraint = market.has_role(Role::Oracle, &authority.key()) @ ZodialError::MissingRole
     )]
     pub market: Account<'info, Market>,
 
@@ -42,7 +46,7 @@ pub struct UpdatePrices<'info> {
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Account<'info, PriceCache>,
+    pub price_cache: AccountLoader<'info, PriceCache>,
 }
 
 pub fn update(ctx: Context<UpdatePrices>, args: UpdatePricesArgs) -> Result<()> {
@@ -52,7 +56,9 @@ pub fn update(ctx: Context<UpdatePrices>, args: UpdatePricesArgs) -> Result<()>
     );
 
     let ar = &ctx.accounts.asset_registry;
-    let pc = &mut ctx.accounts.price_cache;
+    let mut pc_data = PriceCache::data_mut(&ctx.accounts.price_cache)?;
+    let mut pc = PriceTable::new(&mut *pc_data)?;
+    let clock = Clock::get()?;
 
     for u in args.updates.iter() {
         let idx = ar
@@ -62,22 +68,27 @@ pub fn update(ctx: Context<UpdatePrices>, args: UpdatePricesArgs) -> Result<()>
             .map(|a| a.index)
             .ok_or(error!(ZodialError::AssetNotRegistered))?;
 
-        if let Some(entry) = pc.prices.iter_mut().find(|e| e.asset_index == idx) {
-            entry.price_q60 = u.price_q60;
-        } else {
-            pc.prices.push(PriceEntry {
-                asset_index: idx,
-                price_q60: u.price_q60,
-            });
-        }
+        // manual prices are published when pushed, with no confidence
+        // interval, and serve as both spot and EMA
+        pc.set_price(
+            idx,
+            PriceEntry::new(
+                (u.price_q60, 0),
+                (u.price_q60, 0),
+                clock.unix_timestamp,
+                clock.slot,
+                PRICE_SOURCE_MANUAL,
+            ),
+        )?;
     }
 
-    pc.last_slot = Clock::get()?.slot;
+    pc.set_last_slot(clock.slot);
 
     emit!(PricesUpdated {
         market: ctx.accounts.market.key(),
         count: args.updates.len() as u16,
-        slot: pc.last_slot,
+        skipped: 0,
+        slot: clock.slot,
     });
 
     Ok(())
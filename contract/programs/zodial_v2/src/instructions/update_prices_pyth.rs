@@ -1,12 +1,17 @@
 use anchor_lang::prelude::*;
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, Price, PriceUpdateV2};
 
 use crate::{
     constants::*,
     error::ZodialError,
     events::PricesUpdated,
-    state::{AssetRegistry, Market, PriceCache, PriceEntry},
-    utils::price::{q60_from_pyth, q60_to_f64, format_pyth_price},
+    state::{
+        AssetMeta, AssetRegistry, Market, PriceCache, PriceEntry, PriceTable, PRICE_SOURCE_PYTH,
+    },
+    utils::{
+        health::max_price_age_secs,
+        price::{format_pyth_price, pyth_conf_within, q60_from_pyth, q60_to_f64},
+    },
 };
 
 /// Update price for a single asset using Pyth Pull oracle
@@ -15,7 +20,8 @@ use crate::{
 /// The price feed ID is looked up from the asset registry
 pub fn update_prices_pyth(ctx: Context<UpdatePricesPyth>, mint: Pubkey) -> Result<()> {
     let registry = &ctx.accounts.asset_registry;
-    let cache = &mut ctx.accounts.price_cache;
+    let mut cache_data = PriceCache::data_mut(&ctx.accounts.price_cache)?;
+    let mut cache = PriceTable::new(&mut *cache_data)?;
     let price_update = &ctx.accounts.price_update;
     let market = &ctx.accounts.market;
 
@@ -28,48 +34,29 @@ pub fn update_prices_pyth(ctx: Context<UpdatePricesPyth>, mint: Pubkey) -> Resul
 
     msg!("Updating price for asset index {} (mint: {})", asset.index, mint);
 
-    // 2. Check if Pyth feed ID is configured
-    let is_empty = asset.pyth_feed_id.iter().all(|&b| b == 0);
-    require!(!is_empty, ZodialError::PythFeedNotSet);
-
-    // 3. Convert hex feed_id bytes to string (e.g., "0xef0d8b6fda2ceba...")
-    let feed_id_str = core::str::from_utf8(&asset.pyth_feed_id)
-        .map_err(|_| error!(ZodialError::InvalidPythFeedId))?;
-
-    msg!("Feed ID: {}", feed_id_str);
-
-    // 4. Get price from PriceUpdateV2 using get_feed_id_from_hex
-    // If price is not available, skip this asset
-    let price = match price_update.get_price_no_older_than(
-        &Clock::get()?,
-        market.pyth_max_age_secs,
-        &get_feed_id_from_hex(feed_id_str)?,
-    ) {
-        Ok(p) => p,
-        Err(e) => {
-            msg!("Skipping asset {}: price not available or stale ({})", asset.index, e);
-            return Ok(());
-        }
+    // 2. Read the asset's feed; if the price is not available, skip this asset
+    let clock = Clock::get()?;
+    let max_age = max_price_age_secs(market, asset);
+    let Some(prices) = read_pyth_price(asset, price_update, max_age, &clock)? else {
+        return Ok(());
     };
-
-    // 5. Convert to Q60
-    let price_q60 = q60_from_pyth(price.price, price.exponent)?;
-
-    // 6. Upsert into cache
-    if let Some(entry) = cache.prices.iter_mut().find(|e| e.asset_index == asset.index) {
-        // Update existing entry
-        entry.price_q60 = price_q60;
-        msg!("Updated existing price entry for asset {}", asset.index);
-    } else {
-        // Add new entry
-        cache.prices.push(PriceEntry {
-            asset_index: asset.index,
-            price_q60,
-        });
-        msg!("Added new price entry for asset {}", asset.index);
+    let price = prices.spot;
+
+    // 3. Reject wide confidence intervals, spot or EMA
+    if !prices.conf_within(asset.max_conf_bps) {
+        msg!(
+            "Pyth conf {} / EMA conf {} exceeds {} bps of price",
+            price.conf,
+            prices.ema.conf,
+            asset.max_conf_bps
+        );
+        return err!(ZodialError::PriceConfidenceTooWide);
     }
 
-    cache.last_slot = Clock::get()?.slot;
+    // 4. Convert to Q60 and write both prices into the asset's cache slot
+    let slot = clock.slot;
+    let price_q60 = store_pyth_price(&mut cache, asset, &prices, slot)?;
+    msg!("Updated price entry for asset {}", asset.index);
 
     // === READABLE LOGGING ===
 
@@ -90,23 +77,105 @@ pub fn update_prices_pyth(ctx: Context<UpdatePricesPyth>, mint: Pubkey) -> Resul
     // Log metadata
     msg!("=== Metadata ===");
     msg!("Confidence: {}", price.conf);
+    msg!("EMA price: {}", prices.ema.price);
     msg!("Publish time: {}", price.publish_time);
-    msg!("Cache last slot: {}", cache.last_slot);
+    msg!("Cache last slot: {}", cache.header().last_slot);
 
     emit!(PricesUpdated {
         market: ctx.accounts.market.key(),
         count: 1,
-        slot: cache.last_slot,
+        skipped: 0,
+        slot: cache.header().last_slot,
     });
 
     Ok(())
 }
 
+/// Spot and EMA price of one Pyth feed, sharing exponent and publish time
+pub(crate) struct PythPrices {
+    pub spot: Price,
+    pub ema: Price,
+}
+
+impl PythPrices {
+    /// Both confidence intervals within `max_conf_bps` of their price
+    pub fn conf_within(&self, max_conf_bps: u16) -> bool {
+        pyth_conf_within(self.spot.price, self.spot.conf, max_conf_bps)
+            && pyth_conf_within(self.ema.price, self.ema.conf, max_conf_bps)
+    }
+}
+
+/// Prices of `asset`'s feed in `price_update`, None (logged) if the update
+/// does not carry a price for the feed within `max_age_secs`
+pub(crate) fn read_pyth_price(
+    asset: &AssetMeta,
+    price_update: &PriceUpdateV2,
+    max_age_secs: u64,
+    clock: &Clock,
+) -> Result<Option<PythPrices>> {
+    // Check if Pyth feed ID is configured
+    let is_empty = asset.pyth_feed_id.iter().all(|&b| b == 0);
+    require!(!is_empty, ZodialError::PythFeedNotSet);
+
+    // Convert hex feed_id bytes to string (e.g., "0xef0d8b6fda2ceba...")
+    let feed_id_str = core::str::from_utf8(&asset.pyth_feed_id)
+        .map_err(|_| error!(ZodialError::InvalidPythFeedId))?;
+
+    match price_update.get_price_no_older_than(
+        clock,
+        max_age_secs,
+        &get_feed_id_from_hex(feed_id_str)?,
+    ) {
+        // the EMA comes from the same message, checked above
+        Ok(spot) => Ok(Some(PythPrices {
+            spot,
+            ema: Price {
+                price: price_update.price_message.ema_price,
+                conf: price_update.price_message.ema_conf,
+                exponent: spot.exponent,
+                publish_time: spot.publish_time,
+            },
+        })),
+        Err(e) => {
+            msg!("Skipping asset {}: price not available or stale ({})", asset.index, e);
+            Ok(None)
+        }
+    }
+}
+
+/// (price, conf) of a Pyth price in Q60
+fn pyth_price_q60(price: &Price) -> Result<(u128, u128)> {
+    let price_q60 = q60_from_pyth(price.price, price.exponent)?;
+    let conf = i64::try_from(price.conf).map_err(|_| error!(ZodialError::MathOverflow))?;
+    let conf_q60 = q60_from_pyth(conf, price.exponent)?;
+    Ok((price_q60, conf_q60))
+}
+
+/// Convert Pyth spot and EMA prices to Q60 and write them with their
+/// confidence and publish time into `asset`'s cache slot. Returns the Q60
+/// spot price.
+pub(crate) fn store_pyth_price(
+    cache: &mut PriceTable<&mut [u8]>,
+    asset: &AssetMeta,
+    prices: &PythPrices,
+    slot: u64,
+) -> Result<u128> {
+    let spot = pyth_price_q60(&prices.spot)?;
+    let ema = pyth_price_q60(&prices.ema)?;
+
+    cache.set_price(
+        asset.index,
+        PriceEntry::new(spot, ema, prices.spot.publish_time, slot, PRICE_SOURCE_PYTH),
+    )?;
+    cache.set_last_slot(slot);
+    Ok(spot.0)
+}
+
 #[derive(Accounts)]
 #[instruction(mint: Pubkey)]
 pub struct UpdatePricesPyth<'info> {
     #[account(
-        seeds = [SEED_MARKET, market.authority.as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump
     )]
     pub market: Account<'info, Market>,
@@ -122,7 +191,7 @@ pub struct UpdatePricesPyth<'info> {
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Account<'info, PriceCache>,
+    pub price_cache: AccountLoader<'info, PriceCache>,
 
     /// The price update account from Pyth (contains multiple feeds)
     pub price_update: Account<'info, PriceUpdateV2>,
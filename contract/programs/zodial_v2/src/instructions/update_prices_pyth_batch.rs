@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{
+    constants::*,
+    error::ZodialError,
+    events::PricesUpdated,
+    instructions::update_prices_pyth::{read_pyth_price, store_pyth_price, PythPrices},
+    state::{AssetMeta, AssetRegistry, Market, PriceCache, PriceTable},
+    utils::health::max_price_age_secs,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdatePricesPythBatchArgs {
+    /// mints[i] is priced from remaining_accounts[i]
+    pub mints: Vec<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: UpdatePricesPythBatchArgs)]
+pub struct UpdatePricesPythBatch<'info> {
+    #[account(
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
+        bump = market.bump
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [SEED_ASSET_REG, market.key().as_ref()],
+        bump = asset_registry.bump
+    )]
+    pub asset_registry: Account<'info, AssetRegistry>,
+
+    #[account(
+        mut,
+        seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
+        bump = market.price_cache_bump
+    )]
+    pub price_cache: AccountLoader<'info, PriceCache>,
+    // remaining_accounts: one PriceUpdateV2 per entry of `args.mints`, in order
+}
+
+/// Permissionless Pyth update for many assets at once. Every per-entry
+/// failure is logged and counted as skipped, not failed, so one bad entry
+/// cannot block refreshing the rest of the market: an unregistered mint, an
+/// account that is not a Pyth PriceUpdateV2, an asset without a valid feed id,
+/// a missing, stale or too uncertain price, or a price that does not convert
+/// to Q60. Only a mints/accounts length mismatch aborts the batch.
+pub fn update_prices_pyth_batch(
+    ctx: Context<UpdatePricesPythBatch>,
+    args: UpdatePricesPythBatchArgs,
+) -> Result<()> {
+    require!(
+        args.mints.len() == ctx.remaining_accounts.len(),
+        ZodialError::PriceUpdateAccountsMismatch
+    );
+
+    let registry = &ctx.accounts.asset_registry;
+    let market = &ctx.accounts.market;
+    let mut cache_data = PriceCache::data_mut(&ctx.accounts.price_cache)?;
+    let mut cache = PriceTable::new(&mut *cache_data)?;
+    let clock = Clock::get()?;
+
+    let mut updated: u16 = 0;
+    let mut skipped: u16 = 0;
+    for (mint, ai) in args.mints.iter().zip(ctx.remaining_accounts.iter()) {
+        let stored = read_batch_entry(registry, market, mint, ai, &clock).and_then(|entry| {
+            entry
+                .map(|(asset, prices)| store_pyth_price(&mut cache, asset, &prices, clock.slot))
+                .transpose()
+        });
+        match stored {
+            Ok(Some(_)) => updated += 1,
+            Ok(None) => skipped += 1,
+            Err(e) => {
+                msg!("Skipping mint {}: {}", mint, e);
+                skipped += 1;
+            }
+        }
+    }
+
+    msg!("Pyth batch: {} updated, {} skipped", updated, skipped);
+
+    emit!(PricesUpdated {
+        market: market.key(),
+        count: updated,
+        skipped,
+        slot: clock.slot,
+    });
+
+    Ok(())
+}
+
+/// Asset and prices for `mint` from the PriceUpdateV2 in `ai`, None (logged)
+/// if the price is unavailable, stale or too uncertain
+fn read_batch_entry<'a>(
+    registry: &'a AssetRegistry,
+    market: &Market,
+    mint: &Pubkey,
+    ai: &AccountInfo,
+    clock: &Clock,
+) -> Result<Option<(&'a AssetMeta, PythPrices)>> {
+    let asset = registry
+        .assets
+        .iter()
+        .find(|a| a.mint == *mint)
+        .ok_or(error!(ZodialError::AssetNotRegistered))?;
+    // must be a PriceUpdateV2 owned by the Pyth receiver
+    require_keys_eq!(*ai.owner, PriceUpdateV2::owner(), ZodialError::InvalidOwner);
+    let price_update = {
+        let mut data: &[u8] = &ai.data.borrow();
+        PriceUpdateV2::try_deserialize(&mut data)?
+    };
+
+    let max_age = max_price_age_secs(market, asset);
+    let Some(prices) = read_pyth_price(asset, &price_update, max_age, clock)? else {
+        return Ok(None);
+    };
+    if !prices.conf_within(asset.max_conf_bps) {
+        msg!("Skipping asset {}: confidence too wide", asset.index);
+        return Ok(None);
+    }
+    Ok(Some((asset, prices)))
+}
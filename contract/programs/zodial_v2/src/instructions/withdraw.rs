@@ -6,11 +6,12 @@ use crate::{
     error::ZodialError,
     events::Withdraw as WithdrawEvent,
     signer_seeds_vault_auth,
-    state::{AssetRegistry, Market, Obligation, Pool, PriceCache, RiskRegistry},
+    state::{AssetRegistry, Market, Obligation, Pool, PriceCache, PriceTable, RiskMatrix, RiskRegistry},
     utils::{
         accrual::accrue_pool,
         health::assert_healthy_at_least_1,
-        math::{div_u64_by_u68_to_q60, mul_q60_by_u68_to_u64, unpack_u68f60},
+        math::{div_ceil, mul_floor, unpack_u68f60},
+        pools::load_obligation_pools,
     },
 };
 
@@ -26,16 +27,12 @@ pub struct Withdraw<'info> {
     pub owner: Signer<'info>,
 
     #[account(
-        seeds = [SEED_MARKET, authority.key().as_ref()],
+        seeds = [SEED_MARKET, market.pda_seed().as_ref()],
         bump = market.bump,
-        has_one = authority,
         constraint = !market.paused @ ZodialError::MarketPaused
     )]
     pub market: Account<'info, Market>,
 
-    /// CHECK: read only
-    pub authority: UncheckedAccount<'info>,
-
     #[account(
         seeds = [SEED_ASSET_REG, market.key().as_ref()],
         bump = asset_registry.bump
@@ -44,15 +41,15 @@ pub struct Withdraw<'info> {
 
     #[account(
         seeds = [SEED_RISK_REG, market.key().as_ref()],
-        bump = risk_registry.bump
+        bump = risk_registry.load()?.bump
     )]
-    pub risk_registry: Account<'info, RiskRegistry>,
+    pub risk_registry: AccountLoader<'info, RiskRegistry>,
 
     #[account(
         seeds = [SEED_PRICE_CACHE, market.key().as_ref()],
         bump = market.price_cache_bump
     )]
-    pub price_cache: Option<Account<'info, PriceCache>>,
+    pub price_cache: Option<AccountLoader<'info, PriceCache>>,
 
     #[account(
         mut,
@@ -93,25 +90,24 @@ pub struct Withdraw<'info> {
 
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    // remaining_accounts: all other pools for health check
+    // remaining_accounts: all other pools for health check (writable)
 }
 
 pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
-    accrue_pool(&mut ctx.accounts.pool, now);
+    accrue_pool(&mut ctx.accounts.pool, now)?;
 
-    let ob = &mut ctx.accounts.obligation;
-    let pos_idx = ob
-        .positions
-        .iter()
-        .position(|p| p.mint == ctx.accounts.pool.mint)
-        .ok_or(error!(ZodialError::PositionNotFound))?;
-    let mut ob_sim = (*ob).clone();
-    let pos = &mut ob.positions[pos_idx];
+    let pool_mint = ctx.accounts.pool.mint;
+    let deposit_shares_q60 = ctx
+        .accounts
+        .obligation
+        .position(&pool_mint)
+        .ok_or(error!(ZodialError::PositionNotFound))?
+        .deposit_shares_q60;
 
     let d_idx = unpack_u68f60(ctx.accounts.pool.deposit_fac_q60);
-    let available_underlying = mul_q60_by_u68_to_u64(pos.deposit_shares_q60, d_idx)?;
+    let available_underlying = mul_floor(deposit_shares_q60, d_idx)?;
     if available_underlying == 0 {
         return Ok(());
     }
@@ -121,21 +117,14 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
         return Ok(());
     }
 
-    let shares_to_burn_q60 = div_u64_by_u68_to_q60(to_withdraw, d_idx)?;
-    let burn_q60 = shares_to_burn_q60.min(pos.deposit_shares_q60);
+    // burn shares rounded up, pay out rounded down
+    let shares_to_burn_q60 = div_ceil(to_withdraw, d_idx)?;
+    let burn_q60 = shares_to_burn_q60.min(deposit_shares_q60);
 
-    let transfer_amount = mul_q60_by_u68_to_u64(burn_q60, d_idx)?;
+    let transfer_amount = mul_floor(burn_q60, d_idx)?.min(to_withdraw);
 
-    if let Some(sim_pos) = ob_sim
-        .positions
-        .iter_mut()
-        .find(|p| p.mint == ctx.accounts.pool.mint)
-    {
-        sim_pos.deposit_shares_q60 = sim_pos
-            .deposit_shares_q60
-            .checked_sub(burn_q60)
-            .ok_or(error!(ZodialError::MathOverflow))?;
-    }
+    let mut ob_sim = ctx.accounts.obligation.clone();
+    ob_sim.remove_deposit_shares(&pool_mint, burn_q60)?;
 
     let mut pools: Vec<Pool> = Vec::new();
     {
@@ -146,37 +135,39 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
             .ok_or(error!(ZodialError::MathOverflow))?;
         pools.push(touched);
 
-        for ai in ctx.remaining_accounts.iter() {
-            require!(ai.owner == &crate::id(), ZodialError::Unauthorized);
-            let mut data: &[u8] = &ai.data.borrow();
-            let pool = Pool::try_deserialize(&mut data)?;
-            let (expect, _b) = Pubkey::find_program_address(
-                &[
-                    SEED_POOL,
-                    ctx.accounts.market.key().as_ref(),
-                    pool.mint.as_ref(),
-                ],
-                &crate::id(),
-            );
-            require!(expect == *ai.key, ZodialError::Unauthorized);
-            pools.push(pool);
-        }
+        pools.extend(load_obligation_pools(
+            ctx.remaining_accounts,
+            &ctx.accounts.market.key(),
+            &ob_sim,
+            &[ctx.accounts.pool.mint],
+            now,
+        )?);
     }
 
-    let price_cache_ref = ctx.accounts.price_cache.as_ref().map(|a| &**a);
+    let price_cache_data = ctx
+        .accounts
+        .price_cache
+        .as_ref()
+        .map(PriceCache::data)
+        .transpose()?;
+    let price_cache = price_cache_data
+        .as_deref()
+        .map(PriceTable::new)
+        .transpose()?;
+    let price_cache_ref = price_cache.as_ref();
     let _health = assert_healthy_at_least_1(
         &ob_sim,
         &ctx.accounts.market,
         &ctx.accounts.asset_registry,
-        &ctx.accounts.risk_registry,
+        &RiskMatrix::new(&*RiskRegistry::data(&ctx.accounts.risk_registry)?)?,
         price_cache_ref,
         &pools,
+        now,
     )?;
 
-    pos.deposit_shares_q60 = pos
-        .deposit_shares_q60
-        .checked_sub(burn_q60)
-        .ok_or(error!(ZodialError::MathOverflow))?;
+    ctx.accounts
+        .obligation
+        .remove_deposit_shares(&pool_mint, burn_q60)?;
 
     {
         let p = &mut ctx.accounts.pool;
@@ -186,11 +177,6 @@ pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
             .ok_or(error!(ZodialError::MathOverflow))?;
     }
 
-    // drop empty position
-    if pos.deposit_shares_q60 == 0 && pos.borrow_shares_q60 == 0 {
-        ob.positions.swap_remove(pos_idx);
-    }
-
     // transfer vault -> owner
     let key = ctx.accounts.pool.key();
     let seeds = signer_seeds_vault_auth!(key, ctx.accounts.pool.vault_auth_bump);
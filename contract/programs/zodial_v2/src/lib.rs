@@ -20,6 +20,28 @@ pub mod zodial_v2 {
         init_market::init_market(ctx, args)
     }
 
+    pub fn update_market_config(
+        ctx: Context<UpdateMarketConfig>,
+        args: UpdateMarketConfigArgs,
+    ) -> Result<()> {
+        update_market_config::update_market_config(ctx, args)
+    }
+
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        args: ProposeAuthorityArgs,
+    ) -> Result<()> {
+        propose_authority::propose_authority(ctx, args)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        accept_authority::accept_authority(ctx)
+    }
+
+    pub fn set_market_roles(ctx: Context<SetMarketRoles>, args: SetMarketRolesArgs) -> Result<()> {
+        set_market_roles::set_market_roles(ctx, args)
+    }
+
     pub fn register_asset(
         ctx: Context<RegisterAsset>,
         args: register_asset::RegisterAssetArgs,
@@ -27,10 +49,32 @@ pub mod zodial_v2 {
         register_asset::register_asset(ctx, args)
     }
 
+    pub fn update_asset_flags(
+        ctx: Context<UpdateAssetFlags>,
+        args: UpdateAssetFlagsArgs,
+    ) -> Result<()> {
+        update_asset_flags::update_asset_flags(ctx, args)
+    }
+
     pub fn init_pool(ctx: Context<InitPool>, args: InitPoolArgs) -> Result<()> {
         init_pool::init_pool(ctx, args)
     }
 
+    pub fn update_pool_rate_model(
+        ctx: Context<UpdatePoolRateModel>,
+        args: UpdatePoolRateModelArgs,
+    ) -> Result<()> {
+        update_pool_rate_model::update_pool_rate_model(ctx, args)
+    }
+
+    pub fn update_pool_caps(ctx: Context<UpdatePoolCaps>, args: UpdatePoolCapsArgs) -> Result<()> {
+        update_pool_caps::update_pool_caps(ctx, args)
+    }
+
+    pub fn refresh_pools(ctx: Context<RefreshPools>) -> Result<()> {
+        refresh_pools::refresh_pools(ctx)
+    }
+
     pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> Result<()> {
         deposit::deposit(ctx, args)
     }
@@ -54,6 +98,10 @@ pub mod zodial_v2 {
         withdraw::withdraw(ctx, args)
     }
 
+    pub fn claim_reserves(ctx: Context<ClaimReserves>, args: ClaimReservesArgs) -> Result<()> {
+        claim_reserves::claim_reserves(ctx, args)
+    }
+
     pub fn set_risk_pair(ctx: Context<SetRiskPair>, args: SetRiskPairArgs) -> Result<()> {
         set_risk_pair::set(ctx, args)
     }
@@ -65,6 +113,39 @@ pub mod zodial_v2 {
         set_risk_pairs_batch::set_batch(ctx, args)
     }
 
+    pub fn init_param_queue(ctx: Context<InitParamQueue>, args: InitParamQueueArgs) -> Result<()> {
+        init_param_queue::init_param_queue(ctx, args)
+    }
+
+    pub fn queue_param_change(
+        ctx: Context<QueueParamChange>,
+        args: QueueParamChangeArgs,
+    ) -> Result<()> {
+        queue_param_change::queue_param_change(ctx, args)
+    }
+
+    pub fn execute_queued_change(
+        ctx: Context<ExecuteQueuedChange>,
+        args: ExecuteQueuedChangeArgs,
+    ) -> Result<()> {
+        execute_queued_change::execute_queued_change(ctx, args)
+    }
+
+    pub fn cancel_queued_change(
+        ctx: Context<CancelQueuedChange>,
+        args: CancelQueuedChangeArgs,
+    ) -> Result<()> {
+        cancel_queued_change::cancel_queued_change(ctx, args)
+    }
+
+    pub fn migrate_registries(ctx: Context<MigrateRegistries>) -> Result<()> {
+        migrate_registries::migrate_registries(ctx)
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        migrate_pool::migrate_pool(ctx)
+    }
+
     pub fn update_prices(ctx: Context<UpdatePrices>, args: UpdatePricesArgs) -> Result<()> {
         update_prices::update(ctx, args)
     }
@@ -87,6 +168,13 @@ pub mod zodial_v2 {
         update_prices_pyth::update_prices_pyth(ctx, mint)
     }
 
+    pub fn update_prices_pyth_batch(
+        ctx: Context<UpdatePricesPythBatch>,
+        args: UpdatePricesPythBatchArgs,
+    ) -> Result<()> {
+        update_prices_pyth_batch::update_prices_pyth_batch(ctx, args)
+    }
+
     pub fn close_price_cache(ctx: Context<ClosePriceCache>) -> Result<()> {
         close_price_cache::close_price_cache(ctx)
     }
@@ -1,4 +1,7 @@
-use crate::constants::MAX_ASSETS;
+use crate::{
+    constants::{MAX_ASSETS, MAX_CONF_BPS, MAX_CONF_MULTIPLIER_BPS},
+    error::ZodialError,
+};
 use anchor_lang::prelude::*;
 
 #[account]
@@ -11,6 +14,14 @@ pub struct AssetRegistry {
     pub assets: Vec<AssetMeta>,
 }
 
+impl AssetRegistry {
+    /// Account size holding `assets` entries; grows as assets are registered,
+    /// up to MAX_ASSETS
+    pub fn space(assets: usize) -> usize {
+        8 + Self::INIT_SPACE - (MAX_ASSETS - assets.min(MAX_ASSETS)) * AssetMeta::INIT_SPACE
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct AssetMeta {
     pub mint: Pubkey,
@@ -18,5 +29,34 @@ pub struct AssetMeta {
     pub pyth_feed_id: [u8; 66], // Hex string "0xef0d8b..." for Pull oracle, [0u8; 66] if unused
     pub decimals: u8,
     pub enabled_as_collateral: bool,
+    pub enabled_for_borrow: bool,
+    pub max_price_age_secs: u32, // 0 falls back to market.pyth_max_age_secs
+    pub max_conf_bps: u16,       // max conf/price accepted from Pyth, 0 = unchecked
+    pub conf_multiplier_bps: u16, // k in price -/+ k*conf for health, 10000 = 1x conf
+    pub oracle: OracleSource,
     pub index: u16,
 }
+
+impl AssetMeta {
+    /// Confidence filter and health band within MAX_CONF_BPS and
+    /// MAX_CONF_MULTIPLIER_BPS
+    pub fn validate_conf(&self) -> Result<()> {
+        require!(
+            self.max_conf_bps <= MAX_CONF_BPS
+                && self.conf_multiplier_bps <= MAX_CONF_MULTIPLIER_BPS,
+            ZodialError::InvalidConfSettings
+        );
+        Ok(())
+    }
+}
+
+/// Which cached Pyth price an asset is valued at: spot, EMA, or the
+/// conservative of both
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum OracleSource {
+    #[default]
+    Spot,
+    Ema,
+    /// lower of spot and EMA for collateral, higher for debt
+    Conservative,
+}
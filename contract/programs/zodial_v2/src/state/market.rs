@@ -1,9 +1,14 @@
 use anchor_lang::prelude::*;
 
+use crate::state::RiskPair;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Market {
     pub authority: Pubkey,
+    pub pending_authority: Pubkey, // default if no handover in progress
+    pub market_id: u64,            // PDA seed, independent of the authority
+    pub legacy_seed: Pubkey,       // authority seed of a pre-market-id market, default otherwise
     pub max_assets: u16,
     pub max_positions: u16,
     pub default_ltv_bps: u16,
@@ -15,6 +20,66 @@ pub struct Market {
     pub price_cache_bump: u8,
     pub paused: bool,
     pub pyth_max_age_secs: u64,
+    pub roles: MarketRoles,
+}
+
+impl Market {
+    /// Seed after SEED_MARKET: the market id, or the authority a market
+    /// created before market ids was derived from
+    pub fn pda_seed(&self) -> Vec<u8> {
+        if self.legacy_seed == Pubkey::default() {
+            self.market_id.to_le_bytes().to_vec()
+        } else {
+            self.legacy_seed.to_bytes().to_vec()
+        }
+    }
+
+    pub fn default_risk_pair(&self) -> RiskPair {
+        RiskPair {
+            ltv_bps: self.default_ltv_bps,
+            liq_threshold_bps: self.default_liq_threshold_bps,
+            liq_bonus_bps: self.default_liq_bonus_bps,
+        }
+    }
+
+    /// The authority holds every role; an unset role (default key) falls back to it.
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        if *key == self.authority {
+            return true;
+        }
+        let holder = match role {
+            Role::Risk => self.roles.risk_admin,
+            Role::Oracle => self.roles.oracle_admin,
+            Role::PauseGuardian => self.roles.pause_guardian,
+            Role::Treasury => self.roles.treasury,
+        };
+        holder != Pubkey::default() && holder == *key
+    }
+
+    /// Owner of claimed reserves: the treasury role, the authority if unset
+    pub fn treasury(&self) -> Pubkey {
+        match self.roles.treasury {
+            k if k == Pubkey::default() => self.authority,
+            k => k,
+        }
+    }
+}
+
+/// Delegated admin keys, Pubkey::default() if unassigned
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct MarketRoles {
+    pub risk_admin: Pubkey,     // assets, pools, risk pairs, risk defaults
+    pub oracle_admin: Pubkey,   // manual price pushes
+    pub pause_guardian: Pubkey, // can pause, cannot unpause
+    pub treasury: Pubkey,       // protocol reserves
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Risk,
+    Oracle,
+    PauseGuardian,
+    Treasury,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
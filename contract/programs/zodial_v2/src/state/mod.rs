@@ -2,6 +2,7 @@ pub mod asset_registry;
 pub mod faucet_mint;
 pub mod market;
 pub mod obligation;
+pub mod param_queue;
 pub mod pool;
 pub mod price_cache;
 pub mod rate_model;
@@ -11,6 +12,7 @@ pub use asset_registry::*;
 pub use faucet_mint::*;
 pub use market::*;
 pub use obligation::*;
+pub use param_queue::*;
 pub use pool::*;
 pub use price_cache::*;
 pub use rate_model::*;
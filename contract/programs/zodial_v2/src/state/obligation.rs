@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::MAX_POSITIONS;
+use crate::{constants::MAX_POSITIONS, error::ZodialError};
 
 #[account]
 #[derive(InitSpace)]
@@ -18,3 +18,112 @@ pub struct Position {
     pub deposit_shares_q60: u128,
     pub borrow_shares_q60: u128,
 }
+
+impl Obligation {
+    /// Account size holding `positions` positions; accounts start small and grow on push
+    pub fn space(positions: usize) -> usize {
+        8 + Self::INIT_SPACE - (MAX_POSITIONS - positions.min(MAX_POSITIONS)) * Position::INIT_SPACE
+    }
+
+    pub fn position(&self, mint: &Pubkey) -> Option<&Position> {
+        self.positions.iter().find(|p| p.mint == *mint)
+    }
+
+    /// Credit deposit shares, opening a position for `mint` if there is none
+    pub fn add_deposit_shares(
+        &mut self,
+        mint: Pubkey,
+        shares_q60: u128,
+        max_positions: u16,
+    ) -> Result<()> {
+        if shares_q60 == 0 {
+            return Ok(());
+        }
+        let pos = self.position_or_open(mint, max_positions)?;
+        pos.deposit_shares_q60 = pos
+            .deposit_shares_q60
+            .checked_add(shares_q60)
+            .ok_or(error!(ZodialError::MathOverflow))?;
+        Ok(())
+    }
+
+    /// Credit borrow shares, opening a position for `mint` if there is none
+    pub fn add_borrow_shares(
+        &mut self,
+        mint: Pubkey,
+        shares_q60: u128,
+        max_positions: u16,
+    ) -> Result<()> {
+        if shares_q60 == 0 {
+            return Ok(());
+        }
+        let pos = self.position_or_open(mint, max_positions)?;
+        pos.borrow_shares_q60 = pos
+            .borrow_shares_q60
+            .checked_add(shares_q60)
+            .ok_or(error!(ZodialError::MathOverflow))?;
+        Ok(())
+    }
+
+    /// Debit deposit shares, dropping the position once it is empty
+    pub fn remove_deposit_shares(&mut self, mint: &Pubkey, shares_q60: u128) -> Result<()> {
+        let i = self.index_of(mint)?;
+        let pos = &mut self.positions[i];
+        pos.deposit_shares_q60 = pos
+            .deposit_shares_q60
+            .checked_sub(shares_q60)
+            .ok_or(error!(ZodialError::MathUnderflow))?;
+        self.drop_if_empty(i);
+        Ok(())
+    }
+
+    /// Debit borrow shares, dropping the position once it is empty
+    pub fn remove_borrow_shares(&mut self, mint: &Pubkey, shares_q60: u128) -> Result<()> {
+        let i = self.index_of(mint)?;
+        let pos = &mut self.positions[i];
+        pos.borrow_shares_q60 = pos
+            .borrow_shares_q60
+            .checked_sub(shares_q60)
+            .ok_or(error!(ZodialError::MathUnderflow))?;
+        self.drop_if_empty(i);
+        Ok(())
+    }
+
+    fn index_of(&self, mint: &Pubkey) -> Result<usize> {
+        self.positions
+            .iter()
+            .position(|p| p.mint == *mint)
+            .ok_or(error!(ZodialError::PositionNotFound))
+    }
+
+    fn position_or_open(&mut self, mint: Pubkey, max_positions: u16) -> Result<&mut Position> {
+        let i = match self.positions.iter().position(|p| p.mint == mint) {
+            Some(i) => i,
+            None => {
+                require!(
+                    self.positions.len() < (max_positions as usize).min(MAX_POSITIONS),
+                    ZodialError::ExceedsMaxPositions
+                );
+                self.positions.push(Position {
+                    mint,
+                    deposit_shares_q60: 0,
+                    borrow_shares_q60: 0,
+                });
+                self.positions.len() - 1
+            }
+        };
+        Ok(&mut self.positions[i])
+    }
+
+    fn drop_if_empty(&mut self, i: usize) {
+        if self.positions[i].is_empty() {
+            self.positions.swap_remove(i);
+        }
+    }
+}
+
+impl Position {
+    pub fn is_empty(&self) -> bool {
+        self.deposit_shares_q60 == 0 && self.borrow_shares_q60 == 0
+    }
+}
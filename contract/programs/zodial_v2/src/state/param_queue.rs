@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::MAX_QUEUED_CHANGES, state::RateModel};
+
+#[account]
+#[derive(InitSpace)]
+pub struct ParamChangeQueue {
+    pub market: Pubkey,
+    pub bump: u8,
+    pub delay_secs: i64,
+    pub next_id: u64,
+    #[max_len(MAX_QUEUED_CHANGES)]
+    pub changes: Vec<QueuedChange>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct QueuedChange {
+    pub id: u64,
+    pub eta: i64, // earliest unix timestamp for execute
+    pub change: ParamChange,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub enum ParamChange {
+    /// `a_index` is the collateral asset, `b_index` the debt asset
+    RiskPair {
+        a_index: u16,
+        b_index: u16,
+        ltv_bps: u16,
+        liq_threshold_bps: u16,
+        liq_bonus_bps: u16,
+    },
+    PoolRateModel {
+        mint: Pubkey,
+        rate: RateModel,
+    },
+    // authority only, at least MIN_PARAM_DELAY_SECS; shortening the delay
+    // must itself wait out the current delay
+    Delay {
+        delay_secs: i64,
+    },
+}
@@ -2,8 +2,9 @@ use anchor_lang::prelude::*;
 use fixed::types::U68F60;
 
 use crate::constants::BPS_DENOM;
+use crate::error::ZodialError;
 use crate::state::RateModel;
-use crate::utils::math::{mul_q60_by_u68_to_u64, unpack_u68f60};
+use crate::utils::math::{mul_ceil, mul_floor, unpack_u68f60};
 
 #[account]
 #[derive(InitSpace)]
@@ -22,6 +23,13 @@ pub struct Pool {
 
     pub rate: RateModel,
 
+    // underlying atoms, 0 = uncapped
+    pub supply_cap: u64,
+    pub borrow_cap: u64,
+
+    // protocol share of borrow interest, underlying amount in Q60
+    pub accumulated_reserves_q60: u128,
+
     pub bump: u8,
     pub vault_auth_bump: u8,
 }
@@ -37,6 +45,40 @@ impl Pool {
         unpack_u68f60(self.deposit_fac_q60)
     }
 
+    /// total deposits in underlying atoms, rounded down
+    pub fn total_deposits(&self) -> Result<u64> {
+        mul_floor(self.total_deposit_shares_q60, self.deposit_index())
+    }
+
+    /// total borrows in underlying atoms, rounded up
+    pub fn total_borrows(&self) -> Result<u64> {
+        mul_ceil(self.total_borrow_shares_q60, self.borrow_index())
+    }
+
+    pub fn check_supply_cap(&self, add_amount: u64) -> Result<()> {
+        if self.supply_cap == 0 {
+            return Ok(());
+        }
+        let after = self
+            .total_deposits()?
+            .checked_add(add_amount)
+            .ok_or(error!(ZodialError::MathOverflow))?;
+        require!(after <= self.supply_cap, ZodialError::SupplyCapExceeded);
+        Ok(())
+    }
+
+    pub fn check_borrow_cap(&self, add_amount: u64) -> Result<()> {
+        if self.borrow_cap == 0 {
+            return Ok(());
+        }
+        let after = self
+            .total_borrows()?
+            .checked_add(add_amount)
+            .ok_or(error!(ZodialError::MathOverflow))?;
+        require!(after <= self.borrow_cap, ZodialError::BorrowCapExceeded);
+        Ok(())
+    }
+
     /// utilization in bps: borrows / deposits
     pub fn utilization_bps(&self) -> u16 {
         if self.total_borrow_shares_q60 == 0 || self.total_deposit_shares_q60 == 0 {
@@ -45,8 +87,8 @@ impl Pool {
         let b_idx = self.borrow_index();
         let d_idx = self.deposit_index();
 
-        let borrows = mul_q60_by_u68_to_u64(self.total_borrow_shares_q60, b_idx).unwrap_or(0);
-        let deposits = mul_q60_by_u68_to_u64(self.total_deposit_shares_q60, d_idx).unwrap_or(0);
+        let borrows = mul_ceil(self.total_borrow_shares_q60, b_idx).unwrap_or(0);
+        let deposits = mul_floor(self.total_deposit_shares_q60, d_idx).unwrap_or(0);
 
         if deposits == 0 {
             0
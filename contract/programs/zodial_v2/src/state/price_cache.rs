@@ -1,17 +1,249 @@
+use std::cell::{Ref, RefMut};
+use std::ops::{Deref, DerefMut};
+
 use anchor_lang::prelude::*;
 
-#[account]
-#[derive(InitSpace)]
+use fixed::types::U68F60;
+
+use crate::{
+    constants::BPS_DENOM, error::ZodialError, state::OracleSource,
+    utils::math::mul_q60_by_u68_to_q60,
+};
+
+/// Where a cached price came from
+pub const PRICE_SOURCE_NONE: u8 = 0;
+pub const PRICE_SOURCE_MANUAL: u8 = 1;
+pub const PRICE_SOURCE_PYTH: u8 = 2;
+
+/// Zero-copy header; one PriceEntry per registered asset follows it in the
+/// account, indexed by asset index, a zero price is unset. Read and write
+/// them through `PriceTable`.
+#[account(zero_copy, discriminator = b"pricech5")]
 pub struct PriceCache {
     pub market: Pubkey,
-    pub bump: u8,
     pub last_slot: u64,
-    #[max_len(64)]
-    pub prices: Vec<PriceEntry>,
+    pub bump: u8,
+    pub _padding: [u8; 7],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+const HEADER_LEN: usize = 8 + std::mem::size_of::<PriceCache>();
+const ENTRY_LEN: usize = std::mem::size_of::<PriceEntry>();
+
+impl PriceCache {
+    /// Account size holding `entries` prices
+    pub fn space(entries: usize) -> usize {
+        HEADER_LEN + entries * ENTRY_LEN
+    }
+
+    /// Account data of a loaded cache, for `PriceTable::new`
+    pub fn data<'a>(loader: &'a AccountLoader<PriceCache>) -> Result<Ref<'a, [u8]>> {
+        loader.load()?;
+        Ok(Ref::map(loader.as_ref().try_borrow_data()?, |d| &d[..]))
+    }
+
+    /// Writable account data of a loaded cache, for `PriceTable::new`
+    pub fn data_mut<'a>(loader: &'a AccountLoader<PriceCache>) -> Result<RefMut<'a, [u8]>> {
+        loader.load_mut()?;
+        Ok(RefMut::map(loader.as_ref().try_borrow_mut_data()?, |d| {
+            &mut d[..]
+        }))
+    }
+}
+
+/// Header and entries of a PriceCache, over its whole account data
+pub struct PriceTable<D> {
+    data: D,
+}
+
+impl<D: Deref<Target = [u8]>> PriceTable<D> {
+    pub fn new(data: D) -> Result<Self> {
+        require!(
+            data.len() >= HEADER_LEN && data[..8] == *PriceCache::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self { data })
+    }
+
+    pub fn header(&self) -> PriceCache {
+        bytemuck::pod_read_unaligned(&self.data[8..HEADER_LEN])
+    }
+
+    /// Number of entries the account holds
+    pub fn len(&self) -> usize {
+        (self.data.len() - HEADER_LEN) / ENTRY_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn price_q60(&self, asset_index: u16) -> Option<u128> {
+        self.entry(asset_index).map(|e| e.price_q60)
+    }
+
+    /// None if no price was ever written for the asset
+    pub fn entry(&self, asset_index: u16) -> Option<PriceEntry> {
+        let at = HEADER_LEN + asset_index as usize * ENTRY_LEN;
+        self.data
+            .get(at..at + ENTRY_LEN)
+            .map(bytemuck::pod_read_unaligned::<PriceEntry>)
+            .filter(|e| e.price_q60 != 0)
+    }
+
+    /// Price published no more than `max_age_secs` before `now`
+    pub fn fresh_price_q60(&self, asset_index: u16, now: i64, max_age_secs: u64) -> Result<u128> {
+        Ok(self.fresh_entry(asset_index, now, max_age_secs)?.price_q60)
+    }
+
+    /// Entry published no more than `max_age_secs` before `now`
+    pub fn fresh_entry(&self, asset_index: u16, now: i64, max_age_secs: u64) -> Result<PriceEntry> {
+        let e = self
+            .entry(asset_index)
+            .ok_or(error!(ZodialError::PriceStale))?;
+        let age = now.saturating_sub(e.publish_time);
+        // like Pyth, a publish time slightly ahead of the clock counts as fresh
+        if age > 0 && age as u64 > max_age_secs {
+            msg!(
+                "Price for asset {} published at {}, now {}, max age {}s",
+                asset_index,
+                e.publish_time,
+                now,
+                max_age_secs
+            );
+            return err!(ZodialError::PriceStale);
+        }
+        Ok(e)
+    }
+}
+
+impl<D: DerefMut<Target = [u8]>> PriceTable<D> {
+    pub fn set_price(&mut self, asset_index: u16, entry: PriceEntry) -> Result<()> {
+        let at = HEADER_LEN + asset_index as usize * ENTRY_LEN;
+        self.data
+            .get_mut(at..at + ENTRY_LEN)
+            .ok_or(error!(ZodialError::ExceedsMaxAssets))?
+            .copy_from_slice(bytemuck::bytes_of(&entry));
+        Ok(())
+    }
+
+    pub fn set_last_slot(&mut self, slot: u64) {
+        let mut header = self.header();
+        header.last_slot = slot;
+        self.data[8..HEADER_LEN].copy_from_slice(bytemuck::bytes_of(&header));
+    }
+}
+
+#[zero_copy]
 pub struct PriceEntry {
-    pub asset_index: u16,
     pub price_q60: u128,
+    pub conf_q60: u128, // confidence interval half-width, same units as the price
+    pub ema_price_q60: u128,
+    pub ema_conf_q60: u128,
+    pub publish_time: i64, // unix seconds, as reported by the source
+    pub slot: u64,         // slot the entry was written
+    pub source: u8,        // PRICE_SOURCE_*
+    pub _padding: [u8; 15],
+}
+
+impl PriceEntry {
+    pub fn new(
+        (price_q60, conf_q60): (u128, u128),
+        (ema_price_q60, ema_conf_q60): (u128, u128),
+        publish_time: i64,
+        slot: u64,
+        source: u8,
+    ) -> Self {
+        Self {
+            price_q60,
+            conf_q60,
+            ema_price_q60,
+            ema_conf_q60,
+            publish_time,
+            slot,
+            source,
+            _padding: [0; 15],
+        }
+    }
+
+    /// Price used where one value is needed for both sides: the EMA for
+    /// `OracleSource::Ema`, the spot price otherwise
+    pub fn reference_q60(&self, oracle: OracleSource) -> u128 {
+        match oracle {
+            OracleSource::Ema => self.ema_price_q60,
+            OracleSource::Spot | OracleSource::Conservative => self.price_q60,
+        }
+    }
+
+    /// (collateral, debt) prices for `oracle`, each moved k*conf against the
+    /// holder with k = `multiplier_bps` / 10000. Conservative takes the lower
+    /// of spot and EMA for collateral and the higher for debt.
+    pub fn valuation_q60(&self, oracle: OracleSource, multiplier_bps: u16) -> (u128, u128) {
+        let spot = conf_band_q60(self.price_q60, self.conf_q60, multiplier_bps);
+        let ema = conf_band_q60(self.ema_price_q60, self.ema_conf_q60, multiplier_bps);
+        match oracle {
+            OracleSource::Spot => spot,
+            OracleSource::Ema => ema,
+            OracleSource::Conservative => (spot.0.min(ema.0), spot.1.max(ema.1)),
+        }
+    }
+}
+
+/// (price - k*conf, price + k*conf) with k = `multiplier_bps` / 10000
+fn conf_band_q60(price_q60: u128, conf_q60: u128, multiplier_bps: u16) -> (u128, u128) {
+    let spread = mul_q60_by_u68_to_q60(
+        conf_q60,
+        U68F60::from_num(multiplier_bps) / U68F60::from_num(BPS_DENOM),
+    );
+    (
+        price_q60.saturating_sub(spread),
+        price_q60.saturating_add(spread),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_bytes(entries: usize) -> Vec<u8> {
+        let mut data = vec![0u8; PriceCache::space(entries)];
+        data[..8].copy_from_slice(PriceCache::DISCRIMINATOR);
+        data
+    }
+
+    #[test]
+    fn table_holds_one_entry_per_asset() {
+        let mut data = cache_bytes(2);
+        let mut t = PriceTable::new(&mut data[..]).unwrap();
+        assert_eq!(t.len(), 2);
+        assert_eq!(t.price_q60(0), None);
+        t.set_price(
+            1,
+            PriceEntry::new((7, 1), (6, 1), 100, 5, PRICE_SOURCE_PYTH),
+        )
+        .unwrap();
+        assert!(t
+            .set_price(2, PriceEntry::new((7, 1), (6, 1), 100, 5, 0))
+            .is_err());
+        t.set_last_slot(9);
+
+        // growing the account keeps the entries and adds unset ones
+        data.resize(PriceCache::space(3), 0);
+        let t = PriceTable::new(&data[..]).unwrap();
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.header().last_slot, 9);
+        assert_eq!(t.price_q60(1), Some(7));
+        assert_eq!(t.entry(1).unwrap().ema_price_q60, 6);
+        assert_eq!(t.price_q60(2), None);
+        assert_eq!(t.price_q60(3), None);
+        assert!(t.fresh_entry(1, 130, 30).is_ok());
+        assert!(t.fresh_entry(1, 131, 30).is_err());
+    }
+
+    #[test]
+    fn table_rejects_foreign_data() {
+        let mut data = cache_bytes(1);
+        data[0] ^= 1;
+        assert!(PriceTable::new(&data[..]).is_err());
+        assert!(PriceTable::new(&data[..4]).is_err());
+    }
 }
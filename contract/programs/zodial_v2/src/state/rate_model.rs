@@ -1,8 +1,21 @@
-use crate::constants::{BPS_DENOM, MAX_BORROW_APY_BPS_HARD, SECS_YEAR};
+use crate::constants::{
+    BPS_DENOM, MAX_ADJUSTMENT_SPEED_BPS, MAX_BORROW_APY_BPS_HARD, MAX_RATE_CURVE_POINTS, SECS_YEAR,
+};
+use crate::error::ZodialError;
+use crate::utils::math::pow_u68f60;
 use anchor_lang::prelude::*;
+use fixed::types::U68F60;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
-pub struct RateModel {
+pub enum RateModel {
+    Kinked(KinkedCurve),
+    Piecewise(PiecewiseCurve),
+    Adaptive(AdaptiveCurve),
+}
+
+/// Two-slope curve with a single kink
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct KinkedCurve {
     pub kink_util_bps: u16,       // 0..10000
     pub base_borrow_apy_bps: u16, // at 0% util
     pub slope1_bps: u16,          // up to kink
@@ -11,34 +24,129 @@ pub struct RateModel {
     pub max_borrow_apy_bps: u16,  // soft cap
 }
 
+/// Linear interpolation between (util, apy) points, flat past the last one
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PiecewiseCurve {
+    #[max_len(MAX_RATE_CURVE_POINTS)]
+    pub points: Vec<RatePoint>, // strictly increasing util, first at 0
+    pub reserve_factor_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct RatePoint {
+    pub util_bps: u16,
+    pub apy_bps: u16,
+}
+
+/// Curve pinned at `rate_at_target` for target utilization, which drifts up
+/// while util > target and down while util < target.
+/// apy(0) = rat / steepness, apy(target) = rat, apy(100%) = rat * steepness
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct AdaptiveCurve {
+    pub target_util_bps: u16,      // 1..9999
+    pub steepness: u8,             // >= 1
+    pub adjustment_speed_bps: u32, // relative drift of rat per year at full error
+    pub min_rate_at_target_bps: u16,
+    pub max_rate_at_target_bps: u16,
+    pub reserve_factor_bps: u16,
+    pub rate_at_target_q60: u128, // state: current rate at target, bps in Q60
+}
+
 impl RateModel {
+    pub fn reserve_factor_bps(&self) -> u16 {
+        match self {
+            RateModel::Kinked(c) => c.reserve_factor_bps,
+            RateModel::Piecewise(c) => c.reserve_factor_bps,
+            RateModel::Adaptive(c) => c.reserve_factor_bps,
+        }
+    }
+
+    /// bounds check for admin-supplied curves
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.reserve_factor_bps() as u64 <= BPS_DENOM,
+            ZodialError::InvalidRateModel
+        );
+        match self {
+            RateModel::Kinked(c) => {
+                require!(
+                    c.kink_util_bps as u64 <= BPS_DENOM,
+                    ZodialError::InvalidRateModel
+                );
+                require!(
+                    c.max_borrow_apy_bps <= MAX_BORROW_APY_BPS_HARD,
+                    ZodialError::InvalidRateModel
+                );
+                require!(
+                    c.base_borrow_apy_bps <= c.max_borrow_apy_bps,
+                    ZodialError::InvalidRateModel
+                );
+            }
+            RateModel::Piecewise(c) => {
+                require!(
+                    c.points.len() >= 2 && c.points.len() <= MAX_RATE_CURVE_POINTS,
+                    ZodialError::InvalidRateModel
+                );
+                require!(c.points[0].util_bps == 0, ZodialError::InvalidRateModel);
+                for w in c.points.windows(2) {
+                    require!(w[0].util_bps < w[1].util_bps, ZodialError::InvalidRateModel);
+                }
+                for p in c.points.iter() {
+                    require!(
+                        p.util_bps as u64 <= BPS_DENOM && p.apy_bps <= MAX_BORROW_APY_BPS_HARD,
+                        ZodialError::InvalidRateModel
+                    );
+                }
+            }
+            RateModel::Adaptive(c) => {
+                require!(
+                    c.target_util_bps > 0 && (c.target_util_bps as u64) < BPS_DENOM,
+                    ZodialError::InvalidRateModel
+                );
+                require!(c.steepness >= 1, ZodialError::InvalidRateModel);
+                require!(
+                    c.adjustment_speed_bps <= MAX_ADJUSTMENT_SPEED_BPS,
+                    ZodialError::InvalidRateModel
+                );
+                require!(
+                    c.min_rate_at_target_bps <= c.max_rate_at_target_bps
+                        && c.max_rate_at_target_bps <= MAX_BORROW_APY_BPS_HARD,
+                    ZodialError::InvalidRateModel
+                );
+                let rat = U68F60::from_bits(c.rate_at_target_q60);
+                require!(
+                    rat >= U68F60::from_num(c.min_rate_at_target_bps)
+                        && rat <= U68F60::from_num(c.max_rate_at_target_bps),
+                    ZodialError::InvalidRateModel
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// utilization -> borrow apy
     pub fn borrow_apy_bps(&self, util_bps: u16) -> u16 {
-        let util = util_bps as u64;
-        let kink = self.kink_util_bps as u64;
-        let mut apy = self.base_borrow_apy_bps as u64;
+        let apy = match self {
+            RateModel::Kinked(c) => c.borrow_apy_bps(util_bps),
+            RateModel::Piecewise(c) => c.borrow_apy_bps(util_bps),
+            RateModel::Adaptive(c) => c.borrow_apy_bps(util_bps),
+        };
+        apy.min(MAX_BORROW_APY_BPS_HARD)
+    }
 
-        if util <= kink {
-            apy = apy.saturating_add((util.saturating_mul(self.slope1_bps as u64)) / BPS_DENOM);
-        } else {
-            // -> kink
-            apy = apy.saturating_add((kink.saturating_mul(self.slope1_bps as u64)) / BPS_DENOM);
-            // kink ->
-            let extra = util.saturating_sub(kink);
-            apy = apy.saturating_add((extra.saturating_mul(self.slope2_bps as u64)) / BPS_DENOM);
+    /// move stateful curves after an accrual interval, no-op for static ones
+    pub fn adapt(&mut self, util_bps: u16, elapsed_secs: u64) {
+        if let RateModel::Adaptive(c) = self {
+            c.adapt(util_bps, elapsed_secs);
         }
-
-        let soft = self.max_borrow_apy_bps as u64;
-        let hard = MAX_BORROW_APY_BPS_HARD as u64;
-        apy = apy.min(soft).min(hard);
-        apy as u16
     }
 
     /// deposit apy = borrow apy * utilization * (1 - reserve)
+    /// quote only; accrual credits depositors from realised borrow interest
     pub fn deposit_apy_bps(&self, util_bps: u16) -> u16 {
         let borrow = self.borrow_apy_bps(util_bps) as u64;
         let util = util_bps as u64;
-        let take = self.reserve_factor_bps as u64;
+        let take = self.reserve_factor_bps() as u64;
 
         // borrow * (util/1e4) * (1 - take/1e4)
         let net = borrow
@@ -49,33 +157,456 @@ impl RateModel {
         net as u16
     }
 
-    /// apy(bps) to per-second rate (q16.16)
-    /// then advance factor ~= 1 + r * dt (simple accrual for now)
-    pub fn advance_factor_simple(
+    /// borrow apy(bps) -> per-second rate r in Q60, then factor *= (1 + r)^dt.
+    /// Compounds per second, so one accrual over dt equals any split of dt
+    /// (up to Q60 rounding).
+    pub fn advance_factor_compound(
         &self,
         factor_q60: u128,
         util_bps: u16,
         elapsed_secs: u64,
-        is_borrow: bool,
     ) -> u128 {
-        let apy_bps = if is_borrow {
-            self.borrow_apy_bps(util_bps)
-        } else {
-            self.deposit_apy_bps(util_bps)
-        } as u128;
+        let apy_bps = self.borrow_apy_bps(util_bps) as u128;
 
         if apy_bps == 0 || elapsed_secs == 0 {
             return factor_q60;
         }
 
-        // r_per_sec ~= apy / 10000 / SECS_YEAR
-        // factor *= (1 + r_per_sec * dt) in Q60
-        // -> factor_new = factor + factor * apy_bps * dt / (10000 * SECS_YEAR)
-        let num = (factor_q60)
-            .saturating_mul(apy_bps)
-            .saturating_mul(elapsed_secs as u128);
+        // r_per_sec = apy_bps / (10000 * SECS_YEAR) in Q60
+        let denom = (BPS_DENOM as u128) * (SECS_YEAR as u128);
+        let r = U68F60::from_bits((apy_bps << 60) / denom);
+        let growth = pow_u68f60(U68F60::ONE.saturating_add(r), elapsed_secs);
+
+        U68F60::from_bits(factor_q60)
+            .saturating_mul(growth)
+            .to_bits()
+    }
+
+    /// split realised borrow interest into (depositor share, reserve share).
+    /// depositor share rounds down so the remainder always goes to reserves.
+    pub fn split_interest_q60(&self, interest_q60: u128) -> Result<(u128, u128)> {
+        let take = (self.reserve_factor_bps() as u128).min(BPS_DENOM as u128);
+        let to_depositors = U68F60::from_bits(interest_q60)
+            .checked_mul_int(BPS_DENOM as u128 - take)
+            .ok_or(error!(ZodialError::MathOverflow))?
+            .saturating_div_int(BPS_DENOM as u128)
+            .to_bits();
+        Ok((to_depositors, interest_q60 - to_depositors))
+    }
+}
+
+impl KinkedCurve {
+    pub fn borrow_apy_bps(&self, util_bps: u16) -> u16 {
+        let util = util_bps as u64;
+        let kink = self.kink_util_bps as u64;
+        let mut apy = self.base_borrow_apy_bps as u64;
+
+        if util <= kink {
+            apy = apy.saturating_add((util.saturating_mul(self.slope1_bps as u64)) / BPS_DENOM);
+        } else {
+            // -> kink
+            apy = apy.saturating_add((kink.saturating_mul(self.slope1_bps as u64)) / BPS_DENOM);
+            // kink ->
+            let extra = util.saturating_sub(kink);
+            apy = apy.saturating_add((extra.saturating_mul(self.slope2_bps as u64)) / BPS_DENOM);
+        }
+
+        let soft = self.max_borrow_apy_bps as u64;
+        let hard = MAX_BORROW_APY_BPS_HARD as u64;
+        apy = apy.min(soft).min(hard);
+        apy as u16
+    }
+}
+
+impl PiecewiseCurve {
+    pub fn borrow_apy_bps(&self, util_bps: u16) -> u16 {
+        let Some(last) = self.points.last() else {
+            return 0;
+        };
+        if util_bps >= last.util_bps {
+            return last.apy_bps;
+        }
+        for w in self.points.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            if util_bps < b.util_bps {
+                let dx = (b.util_bps - a.util_bps) as i64;
+                let dy = b.apy_bps as i64 - a.apy_bps as i64;
+                let x = util_bps.saturating_sub(a.util_bps) as i64;
+                return (a.apy_bps as i64 + dy * x / dx).max(0) as u16;
+            }
+        }
+        last.apy_bps
+    }
+}
+
+impl AdaptiveCurve {
+    pub fn borrow_apy_bps(&self, util_bps: u16) -> u16 {
+        let rat = U68F60::from_bits(self.rate_at_target_q60);
+        let steep = U68F60::from_num(self.steepness.max(1));
+        let util = util_bps.min(BPS_DENOM as u16) as u64;
+        let target = self.target_util_bps as u64;
+
+        let apy = if util <= target {
+            // rat/steep -> rat
+            let lo = rat.saturating_div(steep);
+            lo.saturating_add(
+                rat.saturating_sub(lo)
+                    .saturating_mul_int(util as u128)
+                    .saturating_div_int(target.max(1) as u128),
+            )
+        } else {
+            // rat -> rat*steep
+            let hi = rat.saturating_mul(steep);
+            rat.saturating_add(
+                hi.saturating_sub(rat)
+                    .saturating_mul_int((util - target) as u128)
+                    .saturating_div_int((BPS_DENOM - target).max(1) as u128),
+            )
+        };
+        apy.to_num::<u128>().min(MAX_BORROW_APY_BPS_HARD as u128) as u16
+    }
+
+    /// rat *= 1 +/- speed * |err| * dt / year, err = normalised distance from target
+    pub fn adapt(&mut self, util_bps: u16, elapsed_secs: u64) {
+        let util = util_bps.min(BPS_DENOM as u16) as u128;
+        let target = self.target_util_bps as u128;
+        let bps = BPS_DENOM as u128;
+        let (err_bps, up) = if util >= target {
+            ((util - target) * bps / (bps - target).max(1), true)
+        } else {
+            ((target - util) * bps / target.max(1), false)
+        };
+
+        let rat = U68F60::from_bits(self.rate_at_target_q60);
+        // relative move = speed * err * dt / year, kept small before touching rat
+        let rel = U68F60::from_num(self.adjustment_speed_bps as u128 * err_bps)
+            .saturating_div_int(bps * bps)
+            .saturating_mul_int(elapsed_secs as u128)
+            .saturating_div_int(SECS_YEAR as u128);
+        let delta = rat.saturating_mul(rel);
+        let moved = if up {
+            rat.saturating_add(delta)
+        } else {
+            rat.saturating_sub(delta)
+        };
+        let clamped = moved
+            .max(U68F60::from_num(self.min_rate_at_target_bps))
+            .min(U68F60::from_num(self.max_rate_at_target_bps));
+        self.rate_at_target_q60 = clamped.to_bits();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Kinked curve paying a flat `apy_bps` at any utilization
+    fn flat(apy_bps: u16, reserve_factor_bps: u16) -> RateModel {
+        RateModel::Kinked(KinkedCurve {
+            kink_util_bps: 8_000,
+            base_borrow_apy_bps: apy_bps,
+            slope1_bps: 0,
+            slope2_bps: 0,
+            reserve_factor_bps,
+            max_borrow_apy_bps: MAX_BORROW_APY_BPS_HARD,
+        })
+    }
+
+    fn one() -> u128 {
+        U68F60::ONE.to_bits()
+    }
+
+    /// one accrual over dt against dt accruals of one second each; every
+    /// Q60 multiply truncates by at most one ulp, so allow one ulp per step
+    /// plus a little slack for the exponentiation by squaring
+    fn assert_one_step_matches_many(apy_bps: u16, dt: u64) {
+        let rate = flat(apy_bps, 0);
+        let long = rate.advance_factor_compound(one(), 5_000, dt);
+        let mut short = one();
+        for _ in 0..dt {
+            short = rate.advance_factor_compound(short, 5_000, 1);
+        }
+        let tolerance = dt as u128 + 64;
+        assert!(
+            long.abs_diff(short) <= tolerance,
+            "apy {} dt {}: {} vs {} differ by more than {} ulp",
+            apy_bps,
+            dt,
+            long,
+            short,
+            tolerance
+        );
+    }
+
+    #[test]
+    fn compound_zero_elapsed_is_identity() {
+        let rate = flat(1_000, 0);
+        let factor = one() + 12_345;
+        assert_eq!(rate.advance_factor_compound(factor, 5_000, 0), factor);
+    }
+
+    #[test]
+    fn compound_zero_rate_is_identity() {
+        let rate = flat(0, 0);
+        assert_eq!(rate.advance_factor_compound(one(), 5_000, SECS_YEAR), one());
+    }
+
+    #[test]
+    fn compound_one_step_matches_many_steps() {
+        for apy_bps in [1, 500, 2_500, MAX_BORROW_APY_BPS_HARD] {
+            for dt in [2, 60, 3_600, 86_400] {
+                assert_one_step_matches_many(apy_bps, dt);
+            }
+        }
+    }
+
+    #[test]
+    fn compound_one_year_matches_daily_steps_and_closed_form() {
+        for apy_bps in [500u16, 2_500, MAX_BORROW_APY_BPS_HARD] {
+            let rate = flat(apy_bps, 0);
+            let year = rate.advance_factor_compound(one(), 5_000, SECS_YEAR);
+
+            let mut daily = one();
+            for _ in 0..365 {
+                daily = rate.advance_factor_compound(daily, 5_000, 86_400);
+            }
+            // 365 steps, each within a few hundred ulp of the exact product
+            assert!(year.abs_diff(daily) <= 365 * 1_000, "apy {}", apy_bps);
+
+            // against (1 + apy/year)^year in f64; truncating r to Q60 costs
+            // at most year * 2^-60 ~ 3e-11
+            let got = U68F60::from_bits(year).to_num::<f64>();
+            let per_sec = apy_bps as f64 / BPS_DENOM as f64 / SECS_YEAR as f64;
+            let want = (SECS_YEAR as f64 * per_sec.ln_1p()).exp();
+            assert!(
+                (got - want).abs() < 1e-10,
+                "apy {}: {} vs {}",
+                apy_bps,
+                got,
+                want
+            );
+        }
+    }
+
+    #[test]
+    fn split_interest_sends_reserve_factor_and_rounding_to_reserves() {
+        for take in [0u16, 1, 1_000, 5_000, 9_999, 10_000] {
+            let rate = flat(1_000, take);
+            for interest in [0u128, 1, 3, one(), 7 * one() + 5, 1u128 << 100] {
+                let (dep, res) = rate.split_interest_q60(interest).unwrap();
+                assert_eq!(dep + res, interest);
+                let keep = (BPS_DENOM - take as u64) as u128;
+                // floor(interest * keep / 10000), computed without overflow
+                let want = interest / BPS_DENOM as u128 * keep
+                    + interest % BPS_DENOM as u128 * keep / BPS_DENOM as u128;
+                assert_eq!(dep, want, "take {} interest {}", take, interest);
+            }
+        }
+    }
+
+    #[test]
+    fn split_interest_rejects_overflow() {
+        // largest interest whose depositor share still fits in U68F60
+        let rate = flat(1_000, 1_000);
+        let max_ok = u128::MAX / 9_000;
+        assert!(rate.split_interest_q60(max_ok).is_ok());
+        assert!(rate.split_interest_q60(max_ok + 1).is_err());
+        assert!(rate.split_interest_q60(u128::MAX).is_err());
+
+        // nothing left to multiply once reserves take everything
+        let all_reserves = flat(1_000, 10_000);
+        assert_eq!(
+            all_reserves.split_interest_q60(u128::MAX).unwrap(),
+            (0, u128::MAX)
+        );
+    }
+
+    fn piecewise(points: &[(u16, u16)]) -> RateModel {
+        RateModel::Piecewise(PiecewiseCurve {
+            points: points
+                .iter()
+                .map(|&(util_bps, apy_bps)| RatePoint { util_bps, apy_bps })
+                .collect(),
+            reserve_factor_bps: 1_000,
+        })
+    }
+
+    /// target 80%, steepness 4, speed 50x per year, rate at target 10%
+    fn adaptive() -> AdaptiveCurve {
+        AdaptiveCurve {
+            target_util_bps: 8_000,
+            steepness: 4,
+            adjustment_speed_bps: MAX_ADJUSTMENT_SPEED_BPS,
+            min_rate_at_target_bps: 100,
+            max_rate_at_target_bps: 5_000,
+            reserve_factor_bps: 1_000,
+            rate_at_target_q60: U68F60::from_num(1_000).to_bits(),
+        }
+    }
+
+    fn rate_at_target_bps(c: &AdaptiveCurve) -> f64 {
+        U68F60::from_bits(c.rate_at_target_q60).to_num::<f64>()
+    }
+
+    #[test]
+    fn piecewise_interpolates_and_is_flat_past_the_last_point() {
+        let rate = piecewise(&[(0, 200), (5_000, 1_000), (8_000, 3_000)]);
+        for (util, want) in [
+            (0u16, 200u16),
+            (2_500, 600),
+            (4_999, 999),
+            (5_000, 1_000),
+            (6_500, 2_000),
+            (8_000, 3_000),
+            (9_000, 3_000),
+            (10_000, 3_000),
+        ] {
+            assert_eq!(rate.borrow_apy_bps(util), want, "util {}", util);
+        }
+
+        // falling segments interpolate downwards
+        let falling = piecewise(&[(0, 1_000), (10_000, 0)]);
+        assert_eq!(falling.borrow_apy_bps(2_500), 750);
+        assert_eq!(falling.borrow_apy_bps(10_000), 0);
+    }
+
+    #[test]
+    fn adaptive_apy_on_both_sides_of_target() {
+        let curve = RateModel::Adaptive(adaptive());
+        for (util, want) in [
+            // rat / 4 at zero, linear up to rat at target
+            (0u16, 250u16),
+            (4_000, 625),
+            (8_000, 1_000),
+            // linear from rat to rat * 4 at full utilization
+            (9_000, 2_500),
+            (10_000, 4_000),
+            // utilization past 100% is treated as 100%
+            (u16::MAX, 4_000),
+        ] {
+            assert_eq!(curve.borrow_apy_bps(util), want, "util {}", util);
+        }
+
+        // rat * steepness beyond the hard cap is clamped
+        let mut c = adaptive();
+        c.rate_at_target_q60 = U68F60::from_num(4_000).to_bits();
+        assert_eq!(
+            RateModel::Adaptive(c).borrow_apy_bps(10_000),
+            MAX_BORROW_APY_BPS_HARD
+        );
+    }
+
+    #[test]
+    fn adaptive_drifts_towards_utilization() {
+        let hundredth = SECS_YEAR / 100;
+        // speed 50x * full error * 1% of a year = +/- 50%
+        for (util, want) in [
+            (10_000u16, 1_500.0),
+            (9_000, 1_250.0),
+            (8_000, 1_000.0),
+            (4_000, 750.0),
+            (0, 500.0),
+        ] {
+            let mut c = adaptive();
+            c.adapt(util, hundredth);
+            let got = rate_at_target_bps(&c);
+            assert!((got - want).abs() < 1e-6, "util {}: {}", util, got);
+        }
+
+        let mut c = adaptive();
+        c.adapt(10_000, 0);
+        assert_eq!(rate_at_target_bps(&c), 1_000.0);
+    }
+
+    #[test]
+    fn adaptive_drift_is_clamped() {
+        let mut up = adaptive();
+        up.adapt(10_000, SECS_YEAR);
+        assert_eq!(rate_at_target_bps(&up), 5_000.0);
+
+        let mut down = adaptive();
+        down.adapt(0, SECS_YEAR);
+        assert_eq!(rate_at_target_bps(&down), 100.0);
+
+        // a day at the fastest allowed speed stays well inside the bounds
+        let mut day = adaptive();
+        day.adapt(10_000, 86_400);
+        let got = rate_at_target_bps(&day);
+        assert!(got > 1_130.0 && got < 1_140.0, "{}", got);
+    }
+
+    #[test]
+    fn validate_rejects_out_of_bounds_curves() {
+        let kinked = |f: fn(&mut KinkedCurve)| {
+            let mut c = KinkedCurve {
+                kink_util_bps: 8_000,
+                base_borrow_apy_bps: 100,
+                slope1_bps: 1_000,
+                slope2_bps: 5_000,
+                reserve_factor_bps: 1_000,
+                max_borrow_apy_bps: 5_000,
+            };
+            f(&mut c);
+            RateModel::Kinked(c)
+        };
+        let adapt = |f: fn(&mut AdaptiveCurve)| {
+            let mut c = adaptive();
+            f(&mut c);
+            RateModel::Adaptive(c)
+        };
+
+        let ok = [
+            kinked(|_| {}),
+            piecewise(&[(0, 0), (10_000, MAX_BORROW_APY_BPS_HARD)]),
+            adapt(|_| {}),
+        ];
+        for rate in ok {
+            assert!(rate.validate().is_ok());
+        }
+
+        let bad = [
+            ("reserve factor", kinked(|c| c.reserve_factor_bps = 10_001)),
+            ("kink", kinked(|c| c.kink_util_bps = 10_001)),
+            ("soft cap", kinked(|c| c.max_borrow_apy_bps = 10_001)),
+            ("base", kinked(|c| c.base_borrow_apy_bps = 5_001)),
+            ("one point", piecewise(&[(0, 100)])),
+            ("first util", piecewise(&[(1, 100), (5_000, 200)])),
+            (
+                "util order",
+                piecewise(&[(0, 100), (5_000, 200), (5_000, 300)]),
+            ),
+            ("util range", piecewise(&[(0, 100), (10_001, 200)])),
+            ("point apy", piecewise(&[(0, 100), (5_000, 10_001)])),
+            ("target 0", adapt(|c| c.target_util_bps = 0)),
+            ("target 100%", adapt(|c| c.target_util_bps = 10_000)),
+            ("steepness", adapt(|c| c.steepness = 0)),
+            (
+                "speed",
+                adapt(|c| c.adjustment_speed_bps = MAX_ADJUSTMENT_SPEED_BPS + 1),
+            ),
+            ("min above max", adapt(|c| c.min_rate_at_target_bps = 5_001)),
+            ("max cap", adapt(|c| c.max_rate_at_target_bps = 10_001)),
+            (
+                "rat below min",
+                adapt(|c| c.rate_at_target_q60 = U68F60::from_num(99).to_bits()),
+            ),
+            (
+                "rat above max",
+                adapt(|c| c.rate_at_target_q60 = U68F60::from_num(5_001).to_bits()),
+            ),
+        ];
+        for (what, rate) in bad {
+            assert_eq!(
+                rate.validate().unwrap_err(),
+                error!(ZodialError::InvalidRateModel),
+                "{}",
+                what
+            );
+        }
 
-        let denom = (crate::constants::BPS_DENOM as u128) * (SECS_YEAR as u128);
-        factor_q60.saturating_add(num / denom)
+        let too_many: Vec<(u16, u16)> = (0..=MAX_RATE_CURVE_POINTS as u16)
+            .map(|i| (i * 100, 100))
+            .collect();
+        assert!(piecewise(&too_many).validate().is_err());
     }
 }
@@ -1,40 +1,345 @@
+use std::cell::{Ref, RefMut};
+use std::ops::{Deref, DerefMut};
+
 use anchor_lang::prelude::*;
 
-use crate::{constants::MAX_RISK_PAIRS, error::ZodialError};
+use crate::{constants::BPS_DENOM, error::ZodialError};
 
-#[account]
-#[derive(InitSpace)]
+/// Zero-copy header; `dim * dim` pairs follow it in the account, ordered by
+/// `pair_index` so registering an asset appends its pairs and never moves
+/// existing ones. Read and write them through `RiskMatrix`.
+#[account(zero_copy, discriminator = b"riskreg3")]
 pub struct RiskRegistry {
     pub market: Pubkey,
-    pub bump: u8,
     pub dim: u16,
-    #[max_len(MAX_RISK_PAIRS)]
-    pub pairs: Vec<RiskPair>,
+    pub bump: u8,
+    pub _padding: [u8; 1],
 }
 
+const HEADER_LEN: usize = 8 + std::mem::size_of::<RiskRegistry>();
+const PAIR_LEN: usize = std::mem::size_of::<RiskPair>();
+
 impl RiskRegistry {
-    /// Get the risk pair for two assets by their indices
-    pub fn get_pair(&self, a_idx: u16, b_idx: u16) -> Result<&RiskPair> {
-        let k = tri_index(a_idx, b_idx, self.dim);
-        self.pairs
-            .get(k)
-            .ok_or(error!(ZodialError::InvalidRiskPair))
+    /// Account size holding the pairs of `dim` assets
+    pub fn space(dim: u16) -> usize {
+        HEADER_LEN + (dim as usize).pow(2) * PAIR_LEN
+    }
+
+    /// Account data of a loaded registry, for `RiskMatrix::new`
+    pub fn data<'a>(loader: &'a AccountLoader<RiskRegistry>) -> Result<Ref<'a, [u8]>> {
+        loader.load()?;
+        Ok(Ref::map(loader.as_ref().try_borrow_data()?, |d| &d[..]))
+    }
+
+    /// Writable account data of a loaded registry, for `RiskMatrix::new`
+    pub fn data_mut<'a>(loader: &'a AccountLoader<RiskRegistry>) -> Result<RefMut<'a, [u8]>> {
+        loader.load_mut()?;
+        Ok(RefMut::map(loader.as_ref().try_borrow_mut_data()?, |d| {
+            &mut d[..]
+        }))
+    }
+}
+
+/// Header and pairs of a RiskRegistry, over its whole account data
+pub struct RiskMatrix<D> {
+    data: D,
+}
+
+impl<D: Deref<Target = [u8]>> RiskMatrix<D> {
+    pub fn new(data: D) -> Result<Self> {
+        require!(
+            data.len() >= HEADER_LEN && data[..8] == *RiskRegistry::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let matrix = Self { data };
+        require!(
+            matrix.data.len() >= RiskRegistry::space(matrix.dim()),
+            ErrorCode::AccountDidNotDeserialize
+        );
+        Ok(matrix)
+    }
+
+    pub fn header(&self) -> RiskRegistry {
+        bytemuck::pod_read_unaligned(&self.data[8..HEADER_LEN])
+    }
+
+    pub fn dim(&self) -> u16 {
+        self.header().dim
+    }
+
+    /// Get the risk pair for `collateral` backing `debt`, by asset index
+    pub fn get_pair(&self, collateral: u16, debt: u16) -> Result<RiskPair> {
+        let dim = self.dim();
+        require!(collateral < dim && debt < dim, ZodialError::InvalidRiskPair);
+        let at = HEADER_LEN + pair_index(collateral, debt) * PAIR_LEN;
+        Ok(bytemuck::pod_read_unaligned(&self.data[at..at + PAIR_LEN]))
+    }
+
+    /// Pair in force for `collateral` backing `debt`, see `RiskPair::or_default`
+    pub fn effective_pair(
+        &self,
+        collateral: u16,
+        debt: u16,
+        default: &RiskPair,
+    ) -> Result<RiskPair> {
+        Ok(self.get_pair(collateral, debt)?.or_default(default))
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+impl<D: DerefMut<Target = [u8]>> RiskMatrix<D> {
+    fn write_pair(&mut self, index: usize, pair: &RiskPair) {
+        let at = HEADER_LEN + index * PAIR_LEN;
+        self.data[at..at + PAIR_LEN].copy_from_slice(bytemuck::bytes_of(pair));
+    }
+
+    /// Validate and store the risk pair for `collateral` backing `debt`
+    pub fn set_pair(&mut self, collateral: u16, debt: u16, pair: RiskPair) -> Result<()> {
+        check_pair_indices(collateral, debt, self.dim())?;
+        pair.validate()?;
+        self.write_pair(pair_index(collateral, debt), &pair);
+        Ok(())
+    }
+
+    /// `set_pair` limited to changes that loosen the pair in force, which may
+    /// skip the timelock; tightening goes through the param queue
+    pub fn loosen_pair(
+        &mut self,
+        collateral: u16,
+        debt: u16,
+        pair: RiskPair,
+        default: &RiskPair,
+    ) -> Result<()> {
+        check_pair_indices(collateral, debt, self.dim())?;
+        let current = self.effective_pair(collateral, debt, default)?;
+        require!(
+            pair.or_default(default).loosens(&current),
+            ZodialError::RiskPairTightened
+        );
+        self.set_pair(collateral, debt, pair)
+    }
+
+    /// Sync dim with the asset count, pairs involving new assets get `fill`
+    /// in both directions. The account must already hold `space(dim)`.
+    pub fn resize(&mut self, dim: u16, fill: &RiskPair) -> Result<()> {
+        let mut header = self.header();
+        if dim <= header.dim {
+            return Ok(());
+        }
+        require!(
+            self.data.len() >= RiskRegistry::space(dim),
+            ErrorCode::AccountDidNotSerialize
+        );
+        for j in header.dim..dim {
+            for i in 0..=j {
+                self.write_pair(pair_index(i, j), fill);
+                self.write_pair(pair_index(j, i), fill);
+            }
+        }
+        header.dim = dim;
+        self.data[8..HEADER_LEN].copy_from_slice(bytemuck::bytes_of(&header));
+        Ok(())
+    }
+}
+
+#[zero_copy]
 pub struct RiskPair {
     pub ltv_bps: u16,
     pub liq_threshold_bps: u16,
     pub liq_bonus_bps: u16,
 }
 
-pub fn tri_index(i: u16, j: u16, dim: u16) -> usize {
-    let (i, j) = if i <= j {
-        (i as usize, j as usize)
+impl RiskPair {
+    /// ltv <= threshold < 100%, and a liquidation at the threshold must
+    /// seize less than the whole collateral: threshold * (1 + bonus) < 100%.
+    /// The bonus check alone implies threshold < 100%; checking the threshold
+    /// first reports which of the two is wrong.
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.ltv_bps <= self.liq_threshold_bps,
+            ZodialError::LtvAboveThreshold
+        );
+        require!(
+            (self.liq_threshold_bps as u64) < BPS_DENOM,
+            ZodialError::LiqThresholdTooHigh
+        );
+        require!(
+            (self.liq_threshold_bps as u64) * (BPS_DENOM + self.liq_bonus_bps as u64)
+                < BPS_DENOM * BPS_DENOM,
+            ZodialError::LiqBonusTooHigh
+        );
+        Ok(())
+    }
+
+    /// A zero LTV or threshold stands for the market default, as in the
+    /// health check
+    pub fn or_default(&self, default: &RiskPair) -> RiskPair {
+        let pick = |v: u16, d: u16| if v != 0 { v } else { d };
+        RiskPair {
+            ltv_bps: pick(self.ltv_bps, default.ltv_bps),
+            liq_threshold_bps: pick(self.liq_threshold_bps, default.liq_threshold_bps),
+            liq_bonus_bps: self.liq_bonus_bps,
+        }
+    }
+
+    /// No lower LTV or threshold and no higher bonus than `current`, so no
+    /// position moves closer to liquidation or loses more in one
+    pub fn loosens(&self, current: &RiskPair) -> bool {
+        self.ltv_bps >= current.ltv_bps
+            && self.liq_threshold_bps >= current.liq_threshold_bps
+            && self.liq_bonus_bps <= current.liq_bonus_bps
+    }
+}
+
+/// Both indices must address registered assets
+pub fn check_pair_indices(a_idx: u16, b_idx: u16, dim: u16) -> Result<()> {
+    for idx in [a_idx, b_idx] {
+        if idx >= dim {
+            msg!("Risk pair index {} out of range, dim {}", idx, dim);
+            return err!(ZodialError::RiskPairIndexOutOfRange);
+        }
+    }
+    Ok(())
+}
+
+/// Slot of (collateral, debt) in shell order: the pairs of the first n
+/// assets fill the first n*n slots, so a new asset only appends
+pub fn pair_index(collateral: u16, debt: u16) -> usize {
+    let (c, d) = (collateral as usize, debt as usize);
+    if c >= d {
+        c * c + d
     } else {
-        (j as usize, i as usize)
-    };
-    let dim = dim as usize;
-    i * dim - (i * (i + 1)) / 2 + j
+        d * d + d + 1 + c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(ltv_bps: u16, liq_threshold_bps: u16, liq_bonus_bps: u16) -> RiskPair {
+        RiskPair {
+            ltv_bps,
+            liq_threshold_bps,
+            liq_bonus_bps,
+        }
+    }
+
+    fn assert_rejects(p: RiskPair, want: ZodialError) {
+        assert_eq!(p.validate().unwrap_err(), error!(want));
+    }
+
+    #[test]
+    fn validate_ltv_against_threshold() {
+        assert!(pair(8_000, 8_000, 0).validate().is_ok());
+        assert!(pair(0, 0, 0).validate().is_ok());
+        assert_rejects(pair(8_001, 8_000, 0), ZodialError::LtvAboveThreshold);
+    }
+
+    #[test]
+    fn validate_threshold_below_one() {
+        assert!(pair(0, 9_999, 0).validate().is_ok());
+        assert_rejects(pair(0, 10_000, 0), ZodialError::LiqThresholdTooHigh);
+        assert_rejects(pair(0, u16::MAX, 0), ZodialError::LiqThresholdTooHigh);
+    }
+
+    #[test]
+    fn validate_bonus_against_threshold() {
+        // 8000 * (10000 + 2499) = 99_992_000 < 10^8
+        assert!(pair(7_000, 8_000, 2_499).validate().is_ok());
+        // 8000 * (10000 + 2500) = 10^8 seizes the whole collateral
+        assert_rejects(pair(7_000, 8_000, 2_500), ZodialError::LiqBonusTooHigh);
+        assert_rejects(pair(0, 9_999, 2), ZodialError::LiqBonusTooHigh);
+        assert!(pair(0, 9_999, 1).validate().is_ok());
+        assert!(pair(0, 0, u16::MAX).validate().is_ok());
+    }
+
+    #[test]
+    fn loosens_only_in_the_holders_favour() {
+        let current = pair(7_000, 8_000, 500);
+        assert!(current.loosens(&current));
+        assert!(pair(7_500, 8_000, 500).loosens(&current));
+        assert!(pair(7_000, 8_500, 400).loosens(&current));
+        assert!(!pair(6_999, 8_000, 500).loosens(&current));
+        assert!(!pair(7_000, 7_999, 500).loosens(&current));
+        assert!(!pair(7_000, 8_000, 501).loosens(&current));
+        // raising the LTV does not pay for a lower threshold
+        assert!(!pair(7_900, 7_900, 500).loosens(&current));
+    }
+
+    #[test]
+    fn zero_ltv_and_threshold_stand_for_the_default() {
+        let default = pair(6_000, 7_000, 300);
+        let p = pair(0, 0, 0).or_default(&default);
+        assert_eq!(
+            (p.ltv_bps, p.liq_threshold_bps, p.liq_bonus_bps),
+            (6_000, 7_000, 0)
+        );
+        let p = pair(5_000, 0, 100).or_default(&default);
+        assert_eq!(
+            (p.ltv_bps, p.liq_threshold_bps, p.liq_bonus_bps),
+            (5_000, 7_000, 100)
+        );
+        // an explicit pair below the default it replaces tightens
+        assert!(!pair(5_000, 7_000, 300).loosens(&pair(0, 0, 300).or_default(&default)));
+    }
+
+    #[test]
+    fn pair_indices_within_dim() {
+        assert!(check_pair_indices(0, 2, 3).is_ok());
+        assert!(check_pair_indices(2, 0, 3).is_ok());
+        assert!(check_pair_indices(3, 0, 3).is_err());
+        assert!(check_pair_indices(0, 3, 3).is_err());
+        assert!(check_pair_indices(0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn pair_index_fills_each_shell_in_turn() {
+        for n in 1..=40u16 {
+            let mut seen: Vec<usize> = (0..n)
+                .flat_map(|c| (0..n).map(move |d| pair_index(c, d)))
+                .collect();
+            seen.sort_unstable();
+            assert_eq!(seen, (0..(n as usize).pow(2)).collect::<Vec<_>>());
+        }
+    }
+
+    fn registry_bytes(dim: u16) -> Vec<u8> {
+        let mut data = vec![0u8; RiskRegistry::space(dim)];
+        data[..8].copy_from_slice(RiskRegistry::DISCRIMINATOR);
+        data
+    }
+
+    #[test]
+    fn matrix_resize_keeps_existing_pairs() {
+        let fill = pair(6_000, 7_000, 300);
+        let mut data = registry_bytes(2);
+        let mut m = RiskMatrix::new(&mut data[..]).unwrap();
+        m.resize(2, &fill).unwrap();
+        m.set_pair(1, 0, pair(7_000, 8_000, 500)).unwrap();
+        assert!(m.set_pair(2, 0, fill).is_err());
+        // growing past the account size fails until the account is grown
+        assert!(m.resize(3, &fill).is_err());
+
+        data.resize(RiskRegistry::space(3), 0);
+        let mut m = RiskMatrix::new(&mut data[..]).unwrap();
+        m.resize(3, &fill).unwrap();
+        assert_eq!(m.dim(), 3);
+        assert_eq!(m.get_pair(1, 0).unwrap().ltv_bps, 7_000);
+        assert_eq!(m.get_pair(0, 1).unwrap().ltv_bps, 6_000);
+        assert_eq!(m.get_pair(2, 1).unwrap().liq_threshold_bps, 7_000);
+        assert_eq!(m.get_pair(1, 2).unwrap().liq_bonus_bps, 300);
+        assert!(m.get_pair(3, 0).is_err());
+        assert!(m.loosen_pair(1, 0, pair(6_900, 8_000, 500), &fill).is_err());
+        assert!(m.loosen_pair(1, 0, pair(7_100, 8_000, 500), &fill).is_ok());
+    }
+
+    #[test]
+    fn matrix_rejects_foreign_or_short_data() {
+        let mut data = registry_bytes(0);
+        data[0] ^= 1;
+        assert!(RiskMatrix::new(&data[..]).is_err());
+        let mut data = registry_bytes(1);
+        data[8 + 32] = 2; // dim 2 in an account sized for 1
+        assert!(RiskMatrix::new(&data[..]).is_err());
+    }
 }
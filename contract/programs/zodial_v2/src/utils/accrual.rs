@@ -1,18 +1,134 @@
+use anchor_lang::prelude::*;
+use fixed::types::U68F60;
+
 use crate::state::Pool;
+use crate::utils::math::{mul_q60_by_u68_to_q60, unpack_u68f60};
 
-pub fn accrue_pool(pool: &mut Pool, now: i64) {
+/// Advance the borrow index at the curve rate, then credit depositors with
+/// exactly the interest borrowers accrued (minus the reserve share), so
+/// deposits can never outgrow vault + borrows.
+pub fn accrue_pool(pool: &mut Pool, now: i64) -> Result<()> {
     let elapsed = now.saturating_sub(pool.last_timestamp);
     if elapsed <= 0 {
-        return;
+        return Ok(());
     }
     let util_bps = pool.utilization_bps();
-    let new_b =
-        pool.rate
-            .advance_factor_simple(pool.borrow_fac_q60, util_bps, elapsed as u64, true);
-    let new_d =
-        pool.rate
-            .advance_factor_simple(pool.deposit_fac_q60, util_bps, elapsed as u64, false);
+    let old_b = pool.borrow_index();
+    let new_b = pool
+        .rate
+        .advance_factor_compound(pool.borrow_fac_q60, util_bps, elapsed as u64);
+
+    // interest realised on total borrows over the interval
+    let interest_q60 = mul_q60_by_u68_to_q60(pool.total_borrow_shares_q60, unpack_u68f60(new_b))
+        .saturating_sub(mul_q60_by_u68_to_q60(pool.total_borrow_shares_q60, old_b));
+    let (mut to_depositors_q60, mut to_reserves_q60) =
+        pool.rate.split_interest_q60(interest_q60)?;
+
+    if pool.total_deposit_shares_q60 == 0 {
+        to_reserves_q60 = interest_q60;
+        to_depositors_q60 = 0;
+    }
+
+    if to_depositors_q60 > 0 {
+        // d_idx += depositor_interest / total_deposit_shares (rounded down)
+        let inc = U68F60::from_bits(to_depositors_q60)
+            .checked_div(U68F60::from_bits(pool.total_deposit_shares_q60))
+            .unwrap_or(U68F60::ZERO);
+        let credited = mul_q60_by_u68_to_q60(pool.total_deposit_shares_q60, inc);
+        // whatever the index rounding could not credit stays with the protocol
+        to_reserves_q60 =
+            to_reserves_q60.saturating_add(to_depositors_q60.saturating_sub(credited));
+        pool.deposit_fac_q60 = pool.deposit_index().saturating_add(inc).to_bits();
+    }
+
+    pool.rate.adapt(util_bps, elapsed as u64);
+    pool.accumulated_reserves_q60 = pool
+        .accumulated_reserves_q60
+        .saturating_add(to_reserves_q60);
     pool.borrow_fac_q60 = new_b;
-    pool.deposit_fac_q60 = new_d;
     pool.last_timestamp = now;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{BPS_DENOM, MAX_BORROW_APY_BPS_HARD, SECS_YEAR};
+    use crate::state::{KinkedCurve, RateModel};
+    use crate::utils::math::div_floor;
+
+    fn pool(deposits: u64, borrows: u64, reserve_factor_bps: u16) -> Pool {
+        Pool {
+            market: Pubkey::default(),
+            mint: Pubkey::default(),
+            vault: Pubkey::default(),
+            borrow_fac_q60: U68F60::ONE.to_bits(),
+            deposit_fac_q60: U68F60::ONE.to_bits(),
+            total_borrow_shares_q60: div_floor(borrows, U68F60::ONE).unwrap(),
+            total_deposit_shares_q60: div_floor(deposits, U68F60::ONE).unwrap(),
+            last_timestamp: 0,
+            rate: RateModel::Kinked(KinkedCurve {
+                kink_util_bps: 8_000,
+                base_borrow_apy_bps: 200,
+                slope1_bps: 1_000,
+                slope2_bps: 8_000,
+                reserve_factor_bps,
+                max_borrow_apy_bps: MAX_BORROW_APY_BPS_HARD,
+            }),
+            supply_cap: 0,
+            borrow_cap: 0,
+            accumulated_reserves_q60: 0,
+            bump: 0,
+            vault_auth_bump: 0,
+        }
+    }
+
+    #[test]
+    fn accrual_keeps_deposits_within_vault_and_borrows() {
+        let deposits = 1_000_000_000_000u64;
+        for util_bps in [0u64, 1, 3_000, 8_000, 9_500, 10_000] {
+            for reserve_factor_bps in [0u16, 1_000, 5_000, 10_000] {
+                let borrows = deposits / BPS_DENOM * util_bps;
+                // lent out tokens leave the vault
+                let vault = deposits - borrows;
+                let mut p = pool(deposits, borrows, reserve_factor_bps);
+
+                let mut now = 0i64;
+                for dt in [1i64, 13, 3_600, 86_400, SECS_YEAR as i64] {
+                    now += dt;
+                    accrue_pool(&mut p, now).unwrap();
+
+                    let d = p.total_deposits().unwrap() as u128;
+                    let b = p.total_borrows().unwrap() as u128;
+                    assert!(
+                        d <= vault as u128 + b,
+                        "util {} reserve {} t {}: deposits {} > vault {} + borrows {}",
+                        util_bps,
+                        reserve_factor_bps,
+                        now,
+                        d,
+                        vault,
+                        b
+                    );
+                    // reserves come out of the same interest
+                    let r = U68F60::from_bits(p.accumulated_reserves_q60).to_num::<u128>();
+                    assert!(d + r <= vault as u128 + b);
+                }
+                if util_bps > 0 && reserve_factor_bps == 10_000 {
+                    assert_eq!(p.deposit_fac_q60, U68F60::ONE.to_bits());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn accrual_without_time_is_noop() {
+        let mut p = pool(1_000_000, 500_000, 1_000);
+        p.last_timestamp = 100;
+        let before = (p.borrow_fac_q60, p.deposit_fac_q60);
+        accrue_pool(&mut p, 100).unwrap();
+        accrue_pool(&mut p, 50).unwrap();
+        assert_eq!((p.borrow_fac_q60, p.deposit_fac_q60), before);
+        assert_eq!(p.accumulated_reserves_q60, 0);
+    }
 }
@@ -4,9 +4,8 @@ use fixed::types::U68F60;
 use crate::{
     constants::BPS_DENOM,
     error::ZodialError,
-    state::risk_registry::tri_index,
-    state::{AssetRegistry, Market, Obligation, Pool, PriceCache, RiskRegistry},
-    utils::math::{mul_q60_by_u68_to_u64, unpack_u68f60},
+    state::{AssetMeta, AssetRegistry, Market, Obligation, Pool, PriceTable, RiskMatrix},
+    utils::math::{mul_ceil, mul_floor, unpack_u68f60},
 };
 
 #[inline]
@@ -20,63 +19,74 @@ fn amount_to_usd_q60(amount_atomic: u64, decimals: u8, price_q60: u128) -> u128
     v.to_bits()
 }
 
+/// Max age of a cached price for `asset`, the market default if unset
 #[inline]
-fn price_for_index_q60(
+pub fn max_price_age_secs(market: &Market, asset: &AssetMeta) -> u64 {
+    match asset.max_price_age_secs {
+        0 => market.pyth_max_age_secs,
+        secs => secs as u64,
+    }
+}
+
+/// Cached reference price for `asset` (see `PriceEntry::reference_q60`),
+/// rejected with PriceStale when missing or older than the asset's max age
+pub fn price_for_asset_q60(
     market: &Market,
-    price_cache: Option<&PriceCache>,
-    idx: u16,
+    price_cache: Option<&PriceTable<&[u8]>>,
+    asset: &AssetMeta,
+    now: i64,
 ) -> Result<u128> {
     match market.price_mode {
         crate::state::PriceMode::Mock => Ok(U68F60::from_num(1u64).to_bits()),
         crate::state::PriceMode::Cache => {
             let pc = price_cache.ok_or(error!(ZodialError::PriceStale))?;
-            let found = pc
-                .prices
-                .iter()
-                .find(|p| p.asset_index == idx)
-                .ok_or(error!(ZodialError::PriceStale))?;
-            Ok(found.price_q60)
+            let e = pc.fresh_entry(asset.index, now, max_price_age_secs(market, asset))?;
+            Ok(e.reference_q60(asset.oracle))
         }
     }
 }
 
-#[inline]
-fn ltv_for_pair_bps(market: &Market, risk: &RiskRegistry, i: u16, j: u16) -> u16 {
-    if risk.dim == 0 {
-        return market.default_ltv_bps;
-    }
-    if i >= risk.dim || j >= risk.dim {
-        return market.default_ltv_bps;
-    }
-    let k = tri_index(i, j, risk.dim);
-    if let Some(p) = risk.pairs.get(k) {
-        if p.ltv_bps == 0 {
-            market.default_ltv_bps
-        } else {
-            p.ltv_bps
+/// (collateral, debt) price for `asset` from its oracle source, moved
+/// -/+ k*conf with k = `conf_multiplier_bps` / 10000. Same staleness rules as
+/// `price_for_asset_q60`.
+pub fn price_band_for_asset_q60(
+    market: &Market,
+    price_cache: Option<&PriceTable<&[u8]>>,
+    asset: &AssetMeta,
+    now: i64,
+    conf_multiplier_bps: u16,
+) -> Result<(u128, u128)> {
+    match market.price_mode {
+        crate::state::PriceMode::Mock => {
+            let one = U68F60::from_num(1u64).to_bits();
+            Ok((one, one))
+        }
+        crate::state::PriceMode::Cache => {
+            let pc = price_cache.ok_or(error!(ZodialError::PriceStale))?;
+            let e = pc.fresh_entry(asset.index, now, max_price_age_secs(market, asset))?;
+            Ok(e.valuation_q60(asset.oracle, conf_multiplier_bps))
         }
-    } else {
-        market.default_ltv_bps
     }
 }
 
 #[inline]
-fn liq_threshold_for_pair_bps(market: &Market, risk: &RiskRegistry, i: u16, j: u16) -> u16 {
-    if risk.dim == 0 {
-        return market.default_liq_threshold_bps;
-    }
-    if i >= risk.dim || j >= risk.dim {
-        return market.default_liq_threshold_bps;
+fn ltv_for_pair_bps(market: &Market, risk: &RiskMatrix<&[u8]>, collateral: u16, debt: u16) -> u16 {
+    match risk.get_pair(collateral, debt) {
+        Ok(p) if p.ltv_bps != 0 => p.ltv_bps,
+        _ => market.default_ltv_bps,
     }
-    let k = tri_index(i, j, risk.dim);
-    if let Some(p) = risk.pairs.get(k) {
-        if p.liq_threshold_bps == 0 {
-            market.default_liq_threshold_bps
-        } else {
-            p.liq_threshold_bps
-        }
-    } else {
-        market.default_liq_threshold_bps
+}
+
+#[inline]
+fn liq_threshold_for_pair_bps(
+    market: &Market,
+    risk: &RiskMatrix<&[u8]>,
+    collateral: u16,
+    debt: u16,
+) -> u16 {
+    match risk.get_pair(collateral, debt) {
+        Ok(p) if p.liq_threshold_bps != 0 => p.liq_threshold_bps,
+        _ => market.default_liq_threshold_bps,
     }
 }
 
@@ -87,18 +97,14 @@ pub fn compute_health_score_q3(
     obligation: &Obligation,
     market: &Market,
     assets: &AssetRegistry,
-    risk: &RiskRegistry,
-    price_cache: Option<&PriceCache>,
+    risk: &RiskMatrix<&[u8]>,
+    price_cache: Option<&PriceTable<&[u8]>>,
     // all pool mints present in obligation.positions
     pools: &[Pool],
+    now: i64,
 ) -> Result<u128> {
-    let find_asset = |mint: &Pubkey| -> Option<(u16, u8)> {
-        assets
-            .assets
-            .iter()
-            .find(|a| a.mint == *mint)
-            .map(|a| (a.index, a.decimals))
-    };
+    let find_asset =
+        |mint: &Pubkey| -> Option<&AssetMeta> { assets.assets.iter().find(|a| a.mint == *mint) };
     let find_pool = |mint: &Pubkey| -> Option<&Pool> { pools.iter().find(|p| p.mint == *mint) };
 
     let mut deposit_values: Vec<(u16 /*i*/, u128 /*price*/)> = Vec::new();
@@ -106,34 +112,38 @@ pub fn compute_health_score_q3(
     let mut total_deposit_q60: u128 = 0;
     let mut total_borrow_q60: u128 = 0;
 
-    for pos in &obligation.positions {
-        let (asset_idx, decimals) =
-            find_asset(&pos.mint).ok_or(error!(ZodialError::AssetNotRegistered))?;
+    for pos in obligation.positions.iter().filter(|p| !p.is_empty()) {
+        let asset = find_asset(&pos.mint).ok_or(error!(ZodialError::AssetNotRegistered))?;
+        let (asset_idx, decimals, is_collateral) =
+            (asset.index, asset.decimals, asset.enabled_as_collateral);
         let pool = find_pool(&pos.mint).ok_or(error!(ZodialError::PoolNotFound))?;
 
         let d_idx = unpack_u68f60(pool.deposit_fac_q60);
         let b_idx = unpack_u68f60(pool.borrow_fac_q60);
 
         let dep_atomic = if pos.deposit_shares_q60 > 0 {
-            mul_q60_by_u68_to_u64(pos.deposit_shares_q60, d_idx)?
+            mul_floor(pos.deposit_shares_q60, d_idx)?
         } else {
             0
         };
         let bor_atomic = if pos.borrow_shares_q60 > 0 {
-            mul_q60_by_u68_to_u64(pos.borrow_shares_q60, b_idx)?
+            mul_ceil(pos.borrow_shares_q60, b_idx)?
         } else {
             0
         };
 
-        let price_q60 = price_for_index_q60(market, price_cache, asset_idx)?;
+        // collateral at the low end of the confidence band, debt at the high end
+        let (low_q60, high_q60) =
+            price_band_for_asset_q60(market, price_cache, asset, now, asset.conf_multiplier_bps)?;
 
-        if dep_atomic > 0 {
-            let v = amount_to_usd_q60(dep_atomic, decimals, price_q60);
+        // deposits of non-collateral assets do not back any borrow
+        if dep_atomic > 0 && is_collateral {
+            let v = amount_to_usd_q60(dep_atomic, decimals, low_q60);
             total_deposit_q60 = total_deposit_q60.saturating_add(v);
             deposit_values.push((asset_idx, v));
         }
         if bor_atomic > 0 {
-            let v = amount_to_usd_q60(bor_atomic, decimals, price_q60);
+            let v = amount_to_usd_q60(bor_atomic, decimals, high_q60);
             total_borrow_q60 = total_borrow_q60.saturating_add(v);
             borrow_values.push((asset_idx, v));
         }
@@ -178,9 +188,10 @@ pub fn assert_healthy_at_least_1(
     obligation: &Obligation,
     market: &Market,
     assets: &AssetRegistry,
-    risk: &RiskRegistry,
-    price_cache: Option<&PriceCache>,
+    risk: &RiskMatrix<&[u8]>,
+    price_cache: Option<&PriceTable<&[u8]>>,
     pools: &[Pool],
+    now: i64,
 ) -> Result<u128> {
     let health = super::health::compute_health_score_q3(
         obligation,
@@ -189,6 +200,7 @@ pub fn assert_healthy_at_least_1(
         risk,
         price_cache,
         pools,
+        now,
     )?;
     require!(health >= 1000, ZodialError::HealthCheckFailed);
     Ok(health)
@@ -200,17 +212,13 @@ pub fn compute_liquidation_health_score_q3(
     obligation: &Obligation,
     market: &Market,
     assets: &AssetRegistry,
-    risk: &RiskRegistry,
-    price_cache: Option<&PriceCache>,
+    risk: &RiskMatrix<&[u8]>,
+    price_cache: Option<&PriceTable<&[u8]>>,
     pools: &[Pool],
+    now: i64,
 ) -> Result<u128> {
-    let find_asset = |mint: &Pubkey| -> Option<(u16, u8)> {
-        assets
-            .assets
-            .iter()
-            .find(|a| a.mint == *mint)
-            .map(|a| (a.index, a.decimals))
-    };
+    let find_asset =
+        |mint: &Pubkey| -> Option<&AssetMeta> { assets.assets.iter().find(|a| a.mint == *mint) };
     let find_pool = |mint: &Pubkey| -> Option<&Pool> { pools.iter().find(|p| p.mint == *mint) };
 
     let mut deposit_values: Vec<(u16, u128)> = Vec::new();
@@ -218,34 +226,38 @@ pub fn compute_liquidation_health_score_q3(
     let mut total_deposit_q60: u128 = 0;
     let mut total_borrow_q60: u128 = 0;
 
-    for pos in &obligation.positions {
-        let (asset_idx, decimals) =
-            find_asset(&pos.mint).ok_or(error!(ZodialError::AssetNotRegistered))?;
+    for pos in obligation.positions.iter().filter(|p| !p.is_empty()) {
+        let asset = find_asset(&pos.mint).ok_or(error!(ZodialError::AssetNotRegistered))?;
+        let (asset_idx, decimals, is_collateral) =
+            (asset.index, asset.decimals, asset.enabled_as_collateral);
         let pool = find_pool(&pos.mint).ok_or(error!(ZodialError::PoolNotFound))?;
 
         let d_idx = unpack_u68f60(pool.deposit_fac_q60);
         let b_idx = unpack_u68f60(pool.borrow_fac_q60);
 
         let dep_atomic = if pos.deposit_shares_q60 > 0 {
-            mul_q60_by_u68_to_u64(pos.deposit_shares_q60, d_idx)?
+            mul_floor(pos.deposit_shares_q60, d_idx)?
         } else {
             0
         };
         let bor_atomic = if pos.borrow_shares_q60 > 0 {
-            mul_q60_by_u68_to_u64(pos.borrow_shares_q60, b_idx)?
+            mul_ceil(pos.borrow_shares_q60, b_idx)?
         } else {
             0
         };
 
-        let price_q60 = price_for_index_q60(market, price_cache, asset_idx)?;
+        // oracle source only, liquidation does not widen by the confidence band
+        let (collateral_q60, debt_q60) =
+            price_band_for_asset_q60(market, price_cache, asset, now, 0)?;
 
-        if dep_atomic > 0 {
-            let v = amount_to_usd_q60(dep_atomic, decimals, price_q60);
+        // deposits of non-collateral assets do not back any borrow
+        if dep_atomic > 0 && is_collateral {
+            let v = amount_to_usd_q60(dep_atomic, decimals, collateral_q60);
             total_deposit_q60 = total_deposit_q60.saturating_add(v);
             deposit_values.push((asset_idx, v));
         }
         if bor_atomic > 0 {
-            let v = amount_to_usd_q60(bor_atomic, decimals, price_q60);
+            let v = amount_to_usd_q60(bor_atomic, decimals, debt_q60);
             total_borrow_q60 = total_borrow_q60.saturating_add(v);
             borrow_values.push((asset_idx, v));
         }
@@ -283,3 +295,128 @@ pub fn compute_liquidation_health_score_q3(
 
     Ok(health_q3)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_ASSETS,
+        state::{MarketRoles, OracleSource, PriceCache, PriceEntry, PriceMode, PRICE_SOURCE_PYTH},
+    };
+
+    const PUBLISHED: i64 = 1_700_000_000;
+
+    fn q60(v: u64) -> u128 {
+        U68F60::from_num(v).to_bits()
+    }
+
+    fn market() -> Market {
+        Market {
+            authority: Pubkey::default(),
+            pending_authority: Pubkey::default(),
+            market_id: 0,
+            legacy_seed: Pubkey::default(),
+            max_assets: MAX_ASSETS as u16,
+            max_positions: MAX_ASSETS as u16,
+            default_ltv_bps: 7_000,
+            default_liq_threshold_bps: 8_000,
+            default_liq_bonus_bps: 500,
+            price_mode: PriceMode::Cache,
+            version: 0,
+            bump: 0,
+            price_cache_bump: 0,
+            paused: false,
+            pyth_max_age_secs: 60,
+            roles: MarketRoles::default(),
+        }
+    }
+
+    fn asset(oracle: OracleSource, conf_multiplier_bps: u16) -> AssetMeta {
+        AssetMeta {
+            mint: Pubkey::default(),
+            pyth_price: Pubkey::default(),
+            pyth_feed_id: [0; 66],
+            decimals: 6,
+            enabled_as_collateral: true,
+            enabled_for_borrow: true,
+            max_price_age_secs: 0,
+            max_conf_bps: 0,
+            conf_multiplier_bps,
+            oracle,
+            index: 1,
+        }
+    }
+
+    /// Spot 100 +/- 2, EMA 98 +/- 4 for asset 1
+    fn cache() -> Vec<u8> {
+        let mut data = vec![0u8; PriceCache::space(2)];
+        data[..8].copy_from_slice(PriceCache::DISCRIMINATOR);
+        PriceTable::new(&mut data[..])
+            .unwrap()
+            .set_price(
+                1,
+                PriceEntry::new(
+                    (q60(100), q60(2)),
+                    (q60(98), q60(4)),
+                    PUBLISHED,
+                    1,
+                    PRICE_SOURCE_PYTH,
+                ),
+            )
+            .unwrap();
+        data
+    }
+
+    fn band(oracle: OracleSource, k_bps: u16, now: i64) -> Result<(u128, u128)> {
+        let asset = asset(oracle, k_bps);
+        price_band_for_asset_q60(
+            &market(),
+            Some(&PriceTable::new(&cache()[..]).unwrap()),
+            &asset,
+            now,
+            asset.conf_multiplier_bps,
+        )
+    }
+
+    #[test]
+    fn collateral_below_and_debt_above_by_k_conf() {
+        // k = 1.5: 100 -/+ 3
+        assert_eq!(
+            band(OracleSource::Spot, 15_000, PUBLISHED).unwrap(),
+            (q60(97), q60(103))
+        );
+        // k = 0.5 on the EMA: 98 -/+ 2
+        assert_eq!(
+            band(OracleSource::Ema, 5_000, PUBLISHED).unwrap(),
+            (q60(96), q60(100))
+        );
+        // k = 6: 100 -/+ 12
+        assert_eq!(
+            band(OracleSource::Spot, 60_000, PUBLISHED).unwrap(),
+            (q60(88), q60(112))
+        );
+        // k = 0 values both sides at the price
+        assert_eq!(
+            band(OracleSource::Spot, 0, PUBLISHED).unwrap(),
+            (q60(100), q60(100))
+        );
+    }
+
+    #[test]
+    fn conservative_takes_the_worse_side_of_spot_and_ema() {
+        // spot 98..102, EMA 94..102 at k = 1
+        assert_eq!(
+            band(OracleSource::Conservative, 10_000, PUBLISHED).unwrap(),
+            (q60(94), q60(102))
+        );
+    }
+
+    #[test]
+    fn band_uses_the_callers_clock() {
+        assert!(band(OracleSource::Spot, 10_000, PUBLISHED + 60).is_ok());
+        assert_eq!(
+            band(OracleSource::Spot, 10_000, PUBLISHED + 61).unwrap_err(),
+            error!(ZodialError::PriceStale)
+        );
+    }
+}
@@ -9,6 +9,23 @@ pub fn unpack_u68f60(bits: u128) -> U68F60 {
     U68F60::from_bits(bits)
 }
 
+/// base^exp by squaring, saturating
+pub fn pow_u68f60(base: U68F60, exp: u64) -> U68F60 {
+    let mut result = U68F60::ONE;
+    let mut b = base;
+    let mut e = exp;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = result.saturating_mul(b);
+        }
+        e >>= 1;
+        if e > 0 {
+            b = b.saturating_mul(b);
+        }
+    }
+    result
+}
+
 /// amount(u64) * index(U68F60) -> u64 (rounding toward zero)
 pub fn mul_u64_u68_to_u64(amount: u64, idx: U68F60) -> Result<u64> {
     let a = U68F60::from_num(amount);
@@ -17,8 +34,25 @@ pub fn mul_u64_u68_to_u64(amount: u64, idx: U68F60) -> Result<u64> {
     u64::try_from(out).map_err(|_| error!(crate::error::ZodialError::MathOverflow))
 }
 
-/// amount(u64) / index(U68F60) -> u128 shares in Q60 as u128
-pub fn div_u64_by_u68_to_q60(amount: u64, idx: U68F60) -> Result<u128> {
+/// Full 256-bit product of two u128, as (hi, lo)
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let ll = a_lo * b_lo;
+    let lh = a_lo * b_hi;
+    let hl = a_hi * b_lo;
+    let hh = a_hi * b_hi;
+
+    let mid = (ll >> 64) + (lh & MASK) + (hl & MASK);
+    let lo = (ll & MASK) | (mid << 64);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// amount(u64) / index(U68F60) -> shares in Q60 bits, rounded down
+pub fn div_floor(amount: u64, idx: U68F60) -> Result<u128> {
     let a = U68F60::from_num(amount);
     let q = a
         .checked_div(idx)
@@ -26,10 +60,177 @@ pub fn div_u64_by_u68_to_q60(amount: u64, idx: U68F60) -> Result<u128> {
     Ok(q.to_bits()) // return raw Q60 bits for storage as shares
 }
 
-/// shares(Q60 as u128) * index(U68F60) -> u64
-pub fn mul_q60_by_u68_to_u64(shares_q60: u128, idx: U68F60) -> Result<u64> {
-    let s = U68F60::from_bits(shares_q60);
-    let p = s.saturating_mul(idx);
-    let out: u128 = p.to_num();
-    u64::try_from(out).map_err(|_| error!(crate::error::ZodialError::MathOverflow))
+/// amount(u64) / index(U68F60) -> shares in Q60 bits, rounded up
+pub fn div_ceil(amount: u64, idx: U68F60) -> Result<u128> {
+    let q = div_floor(amount, idx)?;
+    // exact iff q * idx == amount << 120 (both sides carry 120 fractional bits)
+    let (hi, lo) = mul_wide(q, idx.to_bits());
+    let (t_hi, t_lo) = ((amount as u128) >> 8, (amount as u128) << 120);
+    if (hi, lo) < (t_hi, t_lo) {
+        q.checked_add(1)
+            .ok_or(error!(crate::error::ZodialError::MathOverflow))
+    } else {
+        Ok(q)
+    }
+}
+
+/// shares(Q60 bits) * index(U68F60) -> u64, rounded down
+pub fn mul_floor(shares_q60: u128, idx: U68F60) -> Result<u64> {
+    let (hi, lo) = mul_wide(shares_q60, idx.to_bits());
+    // drop the 120 fractional bits; anything left above 64 bits overflows
+    require!(hi >> 56 == 0, crate::error::ZodialError::MathOverflow);
+    Ok(((hi << 8) | (lo >> 120)) as u64)
+}
+
+/// shares(Q60 bits) * index(U68F60) -> u64, rounded up
+pub fn mul_ceil(shares_q60: u128, idx: U68F60) -> Result<u64> {
+    let floor = mul_floor(shares_q60, idx)?;
+    let (_, lo) = mul_wide(shares_q60, idx.to_bits());
+    if lo & ((1u128 << 120) - 1) != 0 {
+        floor
+            .checked_add(1)
+            .ok_or(error!(crate::error::ZodialError::MathOverflow))
+    } else {
+        Ok(floor)
+    }
+}
+
+/// shares(Q60 as u128) * index(U68F60) -> underlying amount in Q60 (saturating)
+pub fn mul_q60_by_u68_to_q60(shares_q60: u128, idx: U68F60) -> u128 {
+    U68F60::from_bits(shares_q60).saturating_mul(idx).to_bits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// xorshift64*, deterministic so failures reproduce
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn next_u128(&mut self) -> u128 {
+            ((self.next() as u128) << 64) | self.next() as u128
+        }
+
+        /// amount with a random bit length, so small values show up too
+        fn amount(&mut self) -> u64 {
+            self.next() >> (self.next() % 64)
+        }
+
+        /// index in [1, 1025), as pool indexes only grow from 1
+        fn index(&mut self) -> U68F60 {
+            let bits = (1u128 << 60) + ((self.next_u128() >> 58) & ((1u128 << 70) - 1));
+            U68F60::from_bits(bits)
+        }
+    }
+
+    /// schoolbook product over u32 limbs, as (hi, lo)
+    fn mul_reference(a: u128, b: u128) -> (u128, u128) {
+        let limbs = |x: u128| [0, 32, 64, 96].map(|s| (x >> s) as u32 as u64);
+        let (a, b) = (limbs(a), limbs(b));
+        let mut out = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u64;
+            for j in 0..4 {
+                let t = a[i] * b[j] + out[i + j] + carry;
+                out[i + j] = t & u32::MAX as u64;
+                carry = t >> 32;
+            }
+            out[i + 4] = carry;
+        }
+        let join = |l: &[u64]| {
+            l.iter()
+                .rev()
+                .fold(0u128, |acc, &x| (acc << 32) | x as u128)
+        };
+        (join(&out[4..]), join(&out[..4]))
+    }
+
+    const CASES: usize = 20_000;
+
+    #[test]
+    fn mul_wide_matches_reference() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let edges = [
+            0,
+            1,
+            u64::MAX as u128,
+            1u128 << 64,
+            u128::MAX - 1,
+            u128::MAX,
+        ];
+        for &a in &edges {
+            for &b in &edges {
+                assert_eq!(mul_wide(a, b), mul_reference(a, b), "{} * {}", a, b);
+            }
+        }
+        for _ in 0..CASES {
+            let (a, b) = (rng.next_u128(), rng.next_u128());
+            assert_eq!(mul_wide(a, b), mul_reference(a, b), "{} * {}", a, b);
+        }
+    }
+
+    #[test]
+    fn mul_ceil_is_floor_or_floor_plus_one() {
+        let mut rng = Rng(1);
+        for _ in 0..CASES {
+            let (shares, idx) = (rng.next_u128() >> 40, rng.index());
+            let (Ok(lo), Ok(hi)) = (mul_floor(shares, idx), mul_ceil(shares, idx)) else {
+                continue;
+            };
+            assert!(
+                lo <= hi && hi <= lo + 1,
+                "{} * {}: {} {}",
+                shares,
+                idx,
+                lo,
+                hi
+            );
+        }
+        // exact products do not round up
+        assert_eq!(
+            mul_ceil(U68F60::from_num(6).to_bits(), U68F60::from_num(7)).unwrap(),
+            42
+        );
+    }
+
+    #[test]
+    fn div_ceil_shares_cover_the_amount() {
+        let mut rng = Rng(2);
+        for _ in 0..CASES {
+            let (x, idx) = (rng.amount(), rng.index());
+            let up = div_ceil(x, idx).unwrap();
+            let down = div_floor(x, idx).unwrap();
+            assert!(up == down || up == down + 1);
+            // exact: up * idx >= x, down * idx <= x
+            let x_q120 = ((x as u128) >> 8, (x as u128) << 120);
+            assert!(mul_wide(up, idx.to_bits()) >= x_q120, "{} / {}", x, idx);
+            assert!(mul_wide(down, idx.to_bits()) <= x_q120, "{} / {}", x, idx);
+        }
+    }
+
+    #[test]
+    fn deposit_then_full_withdraw_never_gains() {
+        let mut rng = Rng(3);
+        for _ in 0..CASES {
+            let (amount, idx) = (rng.amount(), rng.index());
+            // deposit: shares round down
+            let shares = div_floor(amount, idx).unwrap();
+            let available = mul_floor(shares, idx).unwrap();
+            assert!(available <= amount, "{} at {}", amount, idx);
+
+            // withdraw everything available: burn rounds up, payout down
+            let burn = div_ceil(available, idx).unwrap();
+            assert!(burn <= shares, "{} at {}", amount, idx);
+            let paid = mul_floor(burn, idx).unwrap().min(available);
+            assert!(paid <= amount);
+        }
+    }
 }
@@ -1,5 +1,7 @@
 pub mod accrual;
 pub mod health;
 pub mod math;
+pub mod pools;
 pub mod price;
+pub mod realloc;
 pub mod seeds;
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::SEED_POOL,
+    error::ZodialError,
+    state::{Obligation, Pool},
+    utils::accrual::accrue_pool,
+};
+
+/// Load one Pool PDA from remaining_accounts, accrue it to `now` and write
+/// the accrued state back. The address is re-derived with the stored bump.
+fn load_accrued_pool(ai: &AccountInfo, market: &Pubkey, now: i64) -> Result<Pool> {
+    // Only accept writable accounts owned by this program
+    require!(ai.owner == &crate::id(), ZodialError::Unauthorized);
+    require!(ai.is_writable, ZodialError::Unauthorized);
+
+    let mut pool = {
+        let mut data: &[u8] = &ai.data.borrow();
+        Pool::try_deserialize(&mut data)?
+    };
+    require_keys_eq!(pool.market, *market, ZodialError::Unauthorized);
+    let expect = Pubkey::create_program_address(
+        &[SEED_POOL, market.as_ref(), pool.mint.as_ref(), &[pool.bump]],
+        &crate::id(),
+    )
+    .map_err(|_| error!(ZodialError::Unauthorized))?;
+    require_keys_eq!(expect, *ai.key, ZodialError::Unauthorized);
+
+    accrue_pool(&mut pool, now)?;
+    {
+        let mut data = ai.try_borrow_mut_data()?;
+        pool.try_serialize(&mut &mut data[..])?;
+    }
+    Ok(pool)
+}
+
+/// Load every Pool PDA passed in remaining_accounts, accrued and persisted.
+/// Duplicates are rejected.
+pub fn load_accrued_pools(
+    remaining_accounts: &[AccountInfo],
+    market: &Pubkey,
+    now: i64,
+) -> Result<Vec<Pool>> {
+    let mut pools: Vec<Pool> = Vec::with_capacity(remaining_accounts.len());
+    for ai in remaining_accounts.iter() {
+        let pool = load_accrued_pool(ai, market, now)?;
+        if pools.iter().any(|p| p.mint == pool.mint) {
+            msg!("Duplicate pool for mint {}", pool.mint);
+            return err!(ZodialError::DuplicatePool);
+        }
+        pools.push(pool);
+    }
+    Ok(pools)
+}
+
+/// Load the pools backing `obligation`'s non-empty positions from
+/// remaining_accounts. `held` are mints whose pools the instruction already
+/// holds as typed accounts; those must not be passed again. The supplied set
+/// must match the remaining position mints exactly.
+pub fn load_obligation_pools(
+    remaining_accounts: &[AccountInfo],
+    market: &Pubkey,
+    obligation: &Obligation,
+    held: &[Pubkey],
+    now: i64,
+) -> Result<Vec<Pool>> {
+    let pools = load_accrued_pools(remaining_accounts, market, now)?;
+
+    for pool in pools.iter() {
+        if held.contains(&pool.mint) {
+            msg!("Duplicate pool for mint {}", pool.mint);
+            return err!(ZodialError::DuplicatePool);
+        }
+        let in_use = obligation
+            .positions
+            .iter()
+            .any(|p| p.mint == pool.mint && !p.is_empty());
+        if !in_use {
+            msg!("Unexpected pool for mint {}", pool.mint);
+            return err!(ZodialError::UnexpectedPool);
+        }
+    }
+
+    for pos in obligation.positions.iter().filter(|p| !p.is_empty()) {
+        if !held.contains(&pos.mint) && !pools.iter().any(|p| p.mint == pos.mint) {
+            msg!("Missing pool for mint {}", pos.mint);
+            return err!(ZodialError::MissingPool);
+        }
+    }
+
+    Ok(pools)
+}
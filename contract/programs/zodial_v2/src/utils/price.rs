@@ -1,6 +1,5 @@
 use anchor_lang::prelude::*;
 use fixed::types::U68F60;
-use super::math::pack_u68f60;
 
 pub trait PriceSource {
     fn price(&self, asset_index: u16) -> Result<U68F60>;
@@ -13,36 +12,61 @@ impl PriceSource for MockPriceSource {
     }
 }
 
+/// Largest |exponent| accepted from Pyth; 10^18 * i64::MAX still fits a u128
+pub const MAX_PYTH_EXPONENT: i32 = 18;
+
 /// Convert Pyth price (integer + exponent) to Q60 fixed-point format
 ///
 /// Pyth format: actual_price = price * 10^exponent
 /// Example: price=123456, exponent=-2 -> 1234.56
 ///
-/// Returns the price as u128 in Q60 format for storage
+/// Integer only: positive exponents scale the integer part, negative ones
+/// divide `price << 60` by 10^-exponent, truncating below 2^-60. Values that
+/// do not fit a U68F60 are an error, never rounded.
 ///
 /// # Arguments
 /// * `price` - The raw price value from Pyth (i64)
-/// * `exponent` - The power of 10 exponent from Pyth (i32)
+/// * `exponent` - The power of 10 exponent from Pyth (i32), within +/-18
 ///
 /// # Returns
 /// * `Result<u128>` - Price in Q60 format (packed U68F60)
 pub fn q60_from_pyth(price: i64, exponent: i32) -> Result<u128> {
     // Validate price is not negative
     require!(price >= 0, crate::error::ZodialError::NegativePythPrice);
+    require!(
+        (-MAX_PYTH_EXPONENT..=MAX_PYTH_EXPONENT).contains(&exponent),
+        crate::error::ZodialError::PythExponentOutOfRange
+    );
+
+    let price = price as u128;
+    let scale = 10u128.pow(exponent.unsigned_abs());
 
-    // Convert to U68F60 fixed-point
-    // Calculate the actual decimal value: price * 10^exponent
-    let price_f64 = if exponent >= 0 {
-        (price as f64) * 10f64.powi(exponent)
+    let bits = if exponent >= 0 {
+        // integer part must fit the 68 integer bits
+        let int = price
+            .checked_mul(scale)
+            .ok_or(error!(crate::error::ZodialError::MathOverflow))?;
+        require!(int >> 68 == 0, crate::error::ZodialError::MathOverflow);
+        int << 60
     } else {
-        (price as f64) / 10f64.powi(-exponent)
+        // price < 2^63, so price << 60 < 2^123
+        (price << 60) / scale
     };
 
-    // Create U68F60 from the calculated price
-    let price_fixed = U68F60::from_num(price_f64);
+    Ok(bits)
+}
 
-    // Pack to u128
-    Ok(pack_u68f60(price_fixed))
+/// Whether a Pyth confidence interval is within `max_conf_bps` of the price.
+/// `conf` shares the price's exponent; 0 disables the check.
+pub fn pyth_conf_within(price: i64, conf: u64, max_conf_bps: u16) -> bool {
+    if max_conf_bps == 0 {
+        return true;
+    }
+    if price <= 0 {
+        return false;
+    }
+    let lhs = (conf as u128) * (crate::constants::BPS_DENOM as u128);
+    lhs <= (price as u128) * (max_conf_bps as u128)
 }
 
 /// Convert Q60 format back to human-readable f64
@@ -66,3 +90,71 @@ pub fn format_pyth_price(price: i64, exponent: i32) -> String {
         format!("{}.{:0width$}", integer_part, fractional_part, width = (-exponent) as usize)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ZodialError;
+
+    const ONE: u128 = 1 << 60;
+
+    #[test]
+    fn q60_from_pyth_exact_bits() {
+        let cases: &[(i64, i32, u128)] = &[
+            // BTC 67123.45678901, remainder below 2^-60 truncated
+            (6_712_345_678_901, -8, 77_388_076_795_598_086_642_117),
+            // 1.234567890123456789, the smallest supported exponent
+            (1_234_567_890_123_456_789, -18, 1_423_359_869_420_436_337),
+            // 10^-18 is just above 2^-60
+            (1, -18, 1),
+            // 0.1 and 123.45: f64 gives ...704 and ...262464
+            (1, -1, 115_292_150_460_684_697),
+            (12_345, -2, 142_328_159_743_715_259_187),
+            (0, -8, 0),
+            (42, 0, 42 * ONE),
+            (5, 3, 5_000 * ONE),
+            (1, 18, 1_000_000_000_000_000_000 * ONE),
+            // largest integer part below 2^68 at exponent 2
+            (
+                2_951_479_051_793_528_258,
+                2,
+                295_147_905_179_352_825_800 * ONE,
+            ),
+        ];
+        for &(price, exponent, bits) in cases {
+            assert_eq!(
+                q60_from_pyth(price, exponent).unwrap(),
+                bits,
+                "{price}e{exponent}"
+            );
+        }
+    }
+
+    #[test]
+    fn q60_from_pyth_beats_f64() {
+        for (price, exponent) in [(1i64, -1i32), (12_345, -2)] {
+            let via_f64 = (price as f64 * 10f64.powi(exponent) * ONE as f64) as u128;
+            assert_ne!(q60_from_pyth(price, exponent).unwrap(), via_f64);
+        }
+    }
+
+    #[test]
+    fn q60_from_pyth_rejects_out_of_range() {
+        let cases: &[(i64, i32, ZodialError)] = &[
+            (1, 19, ZodialError::PythExponentOutOfRange),
+            (1, -19, ZodialError::PythExponentOutOfRange),
+            (1, i32::MIN, ZodialError::PythExponentOutOfRange),
+            (-1, -8, ZodialError::NegativePythPrice),
+            // 295_147_905_179_352_825_900 >= 2^68
+            (2_951_479_051_793_528_259, 2, ZodialError::MathOverflow),
+            (i64::MAX, 18, ZodialError::MathOverflow),
+        ];
+        for &(price, exponent, err) in cases {
+            assert_eq!(
+                q60_from_pyth(price, exponent).unwrap_err(),
+                error!(err),
+                "{price}e{exponent}"
+            );
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+/// Grow a program-owned account to `new_len` bytes, `payer` covers the extra
+/// rent. Accounts already at least `new_len` long are left as they are.
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    if account.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+    if rent > lamports {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent - lamports,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+/// Resize a program-owned account to exactly `new_len` zeroed bytes, topping
+/// up or refunding rent against `payer`. For layout migrations only.
+pub fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+    if rent > lamports {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent - lamports,
+        )?;
+    } else if lamports > rent {
+        let excess = lamports - rent;
+        **account.try_borrow_mut_lamports()? -= excess;
+        **payer.try_borrow_mut_lamports()? += excess;
+    }
+    account.resize(new_len)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    data.fill(0);
+    Ok(())
+}
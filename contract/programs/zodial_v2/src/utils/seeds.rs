@@ -1,10 +1,10 @@
 /// Market
 #[macro_export]
 macro_rules! signer_seeds_market {
-    ($authority:expr, $bump:expr) => {
+    ($pda_seed:expr, $bump:expr) => {
         &[
             $crate::constants::SEED_MARKET,
-            $authority.as_ref(),
+            $pda_seed.as_ref(),
             &[$bump],
         ]
     };